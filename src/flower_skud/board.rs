@@ -1,5 +1,5 @@
 use super::*;
-use rand::{thread_rng, Rng};
+use rand::{Rng, RngCore};
 use std::ops::IndexMut;
 
 #[derive(Clone)]
@@ -61,7 +61,7 @@ impl Board {
         self.moves_since_planting
     }
 
-    pub fn get_random_move(&self, grid: &Grid) -> Option<Move> {
+    pub fn get_random_move(&self, grid: &Grid, rng: &mut dyn RngCore) -> Option<Move> {
         let player = self.next_to_move();
         let (mut reserve, mut played_tiles) = match player {
             Player::Host => (self.reserve_host.clone(), self.played_tiles_host.clone()),
@@ -69,30 +69,30 @@ impl Board {
         };
         if self.move_count < 2 {
             if self.move_count == 0 {
-                loop {
-                    let (tile, amount) = reserve.remove(thread_rng().gen_range(0..reserve.len()));
-                    let Tile::Flower(flower) = tile;
-                    if amount == 0 {
-                        unreachable!("All flower tiles should be in the reserve for move one.")
-                    }
-                    return Some(Move::Planting(flower, Position::new(0, -8).unwrap()));
+                let (tile, amount) = reserve.remove(rng.gen_range(0..reserve.len()));
+                let Tile::Flower(flower) = tile;
+                if amount == 0 {
+                    unreachable!("All flower tiles should be in the reserve for move one.")
                 }
+                return Some(Move::Planting(flower, Position::new(0, -8).unwrap()));
             } else {
-                loop {
-                    let south_gate = grid.index(&Position::new(0, -8).unwrap());
-                    let (tile, _) = south_gate.expect("There should be a Tile in the south gate for the second move.");
-                    let Tile::Flower(flower) = tile;
-                    for (t, a) in reserve {
-                        if t == tile && a == 0 {
-                            unreachable!("All flower tiles should be in the reserve for move two.")
-                        }
+                let south_gate = grid.index(&Position::new(0, -8).unwrap());
+                let (tile, _) = south_gate.expect("There should be a Tile in the south gate for the second move.");
+                let Tile::Flower(flower) = tile;
+                for (t, a) in reserve {
+                    if t == tile && a == 0 {
+                        unreachable!("All flower tiles should be in the reserve for move two.")
                     }
-                    return Some(Move::Planting(flower, Position::new(0, 8).unwrap()));
                 }
+                return Some(Move::Planting(flower, Position::new(0, 8).unwrap()));
             }
         }
-        fn random_plant(grid: &Grid, reserve: &mut Vec<(Tile, u8)>) -> Option<Move> {
-            let index = thread_rng().gen_range(0..reserve.len());
+        fn random_plant(
+            grid: &Grid,
+            reserve: &mut Vec<(Tile, u8)>,
+            rng: &mut dyn RngCore,
+        ) -> Option<Move> {
+            let index = rng.gen_range(0..reserve.len());
             let (Tile::Flower(t), amount) = reserve.index_mut(index);
             #[cfg(debug_assertions)]
             println!("{amount}");
@@ -105,8 +105,8 @@ impl Board {
                     .iter()
                     .map(|g| Move::Planting(*t, g.clone()))
                     .collect();
-                if plants.len() > 0 {
-                    let m = plants.remove(thread_rng().gen_range(0..plants.len()));
+                if !plants.is_empty() {
+                    let m = plants.remove(rng.gen_range(0..plants.len()));
                     Some(m)
                 } else {
                     *reserve = Vec::new();
@@ -118,42 +118,42 @@ impl Board {
             grid: &Grid,
             board: &Board,
             played_tiles: &mut Vec<(Tile, Position)>,
+            rng: &mut dyn RngCore,
         ) -> Option<Move> {
-            let (tile, position) =
-                played_tiles.remove(thread_rng().gen_range(0..played_tiles.len()));
+            let (tile, position) = played_tiles.remove(rng.gen_range(0..played_tiles.len()));
             let possible_moves = all_possibilities_for_piece_to_move(board, grid, tile, position);
-            if possible_moves.len() == 0 {
+            if possible_moves.is_empty() {
                 None
             } else {
                 let m = possible_moves
-                    .get(thread_rng().gen_range(0..possible_moves.len()))
+                    .get(rng.gen_range(0..possible_moves.len()))
                     .unwrap();
                 Some(m.clone())
             }
         }
         loop {
-            return if reserve.len() > 0 && played_tiles.len() > 0 {
-                if thread_rng().gen_ratio(1, 3) {
-                    let mo = random_plant(grid, &mut reserve);
+            return if !reserve.is_empty() && !played_tiles.is_empty() {
+                if rng.gen_ratio(1, 3) {
+                    let mo = random_plant(grid, &mut reserve, rng);
                     if mo.is_none() {
                         continue;
                     };
                     mo
                 } else {
-                    let mo = random_move(grid, self, &mut played_tiles);
+                    let mo = random_move(grid, self, &mut played_tiles, rng);
                     if mo.is_none() {
                         continue;
                     }
                     mo
                 }
-            } else if reserve.len() == 0 && played_tiles.len() > 0 {
-                let mo = random_move(grid, self, &mut played_tiles);
+            } else if reserve.is_empty() && !played_tiles.is_empty() {
+                let mo = random_move(grid, self, &mut played_tiles, rng);
                 if mo.is_none() {
                     continue;
                 }
                 mo
-            } else if played_tiles.len() == 0 && reserve.len() > 0 {
-                let mo = random_plant(grid, &mut reserve);
+            } else if played_tiles.is_empty() && !reserve.is_empty() {
+                let mo = random_plant(grid, &mut reserve, rng);
                 if mo.is_none() {
                     continue;
                 };
@@ -174,36 +174,14 @@ impl Board {
         }
     }
 
-    pub fn finished(
-        &self,
-        harmonie_list: Vec<(Owner, Position, Position)>,
-        perspective: Player,
-    ) -> Option<Output> {
+    /// Whether the guest and the host have closed a ring of harmonies around the center.
+    fn rings(harmonie_list: &[(Owner, Position, Position)]) -> (bool, bool) {
         let mut guest_harmonies = Vec::new();
-        let mut guest_crossing_harmonies = 0;
         let mut host_harmonies = Vec::new();
-        let mut host_crossing_harmonies = 0;
-
-        for harmonie in harmonie_list.into_iter() {
-            let (owner, p1, p2) = harmonie;
-            let ((p1_x, p1_y), (p2_x, p2_y)) = (p1.value(), p2.value());
+        for (owner, p1, p2) in harmonie_list {
             match owner {
-                Owner::Host => {
-                    if (p1_y as isize) * (p2_y as isize) < 0
-                        || (p1_x as isize) * (p2_x as isize) < 0
-                    {
-                        host_crossing_harmonies += 1;
-                    }
-                    host_harmonies.push((p1, p2));
-                }
-                Owner::Guest => {
-                    if (p1_y as isize) * (p2_y as isize) < 0
-                        || (p1_x as isize) * (p2_x as isize) < 0
-                    {
-                        guest_crossing_harmonies += 1;
-                    }
-                    guest_harmonies.push((p1, p2));
-                }
+                Owner::Host => host_harmonies.push((p1.clone(), p2.clone())),
+                Owner::Guest => guest_harmonies.push((p1.clone(), p2.clone())),
             }
         }
 
@@ -211,7 +189,7 @@ impl Board {
             harmonies: Vec<(Position, Position)>,
             ring_fragment: Vec<Position>,
         ) -> Vec<Vec<Position>> {
-            if ring_fragment.len() == 0 {
+            if ring_fragment.is_empty() {
                 panic!("a ring_fragment need at least one element")
             }
             let mut rings = Vec::new();
@@ -260,56 +238,83 @@ impl Board {
                 //MAGIC
                 //Not really i count how often the right edge is crossed upwards / downwards
                 if end.1 <= 0 {
-                    if start.1 > 0 {
-                        if ( (start.0 - end.0) * (0 - end.1) - (0 -  end.0) * (start.1 - end.1) ) > 0 {
-                            winding_number += 1;
-                        }
-                    }
-                } else {
-                    if start.1 <= 0 {
-                        if ( (start.0 - end.0) * (0 - end.1) - (0 -  end.0) * (start.1 - end.1) ) < 0 {
-                            winding_number -= 1;
-                        }
+                    if start.1 > 0
+                        && ( (start.0 - end.0) * (0 - end.1) - (0 -  end.0) * (start.1 - end.1) ) > 0 {
+                        winding_number += 1;
                     }
+                } else if start.1 <= 0
+                    && ( (start.0 - end.0) * (0 - end.1) - (0 -  end.0) * (start.1 - end.1) ) < 0 {
+                    winding_number -= 1;
                 }
             }
             winding_number != 0
         }
 
-        if guest_harmonies.len() + host_harmonies.len() > 0 {
-            let mut guest_won = false;
-            'test_harmony: while let Some(harmony) = guest_harmonies.pop() {
-                let r = vec![harmony.0, harmony.1];
-                for ring in finish_ring(guest_harmonies.clone(), r) {
-                    if ring_contains_center(ring) {
-                        guest_won = true;
-                        break 'test_harmony;
-                    }
+        let mut guest_won = false;
+        'test_harmony: while let Some(harmony) = guest_harmonies.pop() {
+            let r = vec![harmony.0, harmony.1];
+            for ring in finish_ring(guest_harmonies.clone(), r) {
+                if ring_contains_center(ring) {
+                    guest_won = true;
+                    break 'test_harmony;
+                }
+            }
+        }
+
+        let mut host_won = false;
+        'test_harmony: while let Some(harmony) = host_harmonies.pop() {
+            let r = vec![harmony.0, harmony.1];
+            for ring in finish_ring(host_harmonies.clone(), r) {
+                if ring_contains_center(ring) {
+                    host_won = true;
+                    break 'test_harmony;
                 }
             }
+        }
+
+        (guest_won, host_won)
+    }
+
+    pub fn finished(
+        &self,
+        harmonie_list: Vec<(Owner, Position, Position)>,
+        perspective: Player,
+    ) -> Option<Output> {
+        let mut guest_crossing_harmonies = 0;
+        let mut host_crossing_harmonies = 0;
 
-            let mut host_won = false;
-            'test_harmony: while let Some(harmony) = host_harmonies.pop() {
-                let r = vec![harmony.0, harmony.1];
-                for ring in finish_ring(host_harmonies.clone(), r) {
-                    if ring_contains_center(ring) {
-                        host_won = true;
-                        break 'test_harmony;
+        for harmonie in harmonie_list.iter() {
+            let (owner, p1, p2) = harmonie;
+            let ((p1_x, p1_y), (p2_x, p2_y)) = (p1.value(), p2.value());
+            match owner {
+                Owner::Host => {
+                    if (p1_y as isize) * (p2_y as isize) < 0
+                        || (p1_x as isize) * (p2_x as isize) < 0
+                    {
+                        host_crossing_harmonies += 1;
+                    }
+                }
+                Owner::Guest => {
+                    if (p1_y as isize) * (p2_y as isize) < 0
+                        || (p1_x as isize) * (p2_x as isize) < 0
+                    {
+                        guest_crossing_harmonies += 1;
                     }
                 }
             }
+        }
 
-            if host_won || guest_won {
-                return Some(if host_won && guest_won {
-                    Output::Draw
-                } else if host_won && perspective == Player::Host {
-                    Output::Win
-                } else if guest_won && perspective == Player::Guest {
-                    Output::Win
-                } else {
-                    Output::Loss
-                });
-            }
+        let (guest_won, host_won) = Self::rings(&harmonie_list);
+        if host_won || guest_won {
+            return Some(if host_won && guest_won {
+                Output::Draw
+            } else if (host_won && perspective == Player::Host)
+                || (guest_won && perspective == Player::Guest)
+            {
+                Output::Win
+            } else {
+                Output::Loss
+            });
         }
 
         let reserve_size_guest = self
@@ -350,7 +355,57 @@ impl Board {
         if self.moves_since_planting >= 50 {
             return Some(Output::Draw);
         };
-        return None;
+        None
+    }
+
+    /// A guess at the chance that `perspective` wins, from the harmonies on the board.
+    /// Crossing harmonies decide the game once a reserve runs out, and a ring around the center
+    /// has to reach into all four quadrants.
+    pub fn evaluate(&self, harmonie_list: Vec<(Owner, Position, Position)>, perspective: Player) -> f64 {
+        let own = match perspective {
+            Player::Host => Owner::Host,
+            Player::Guest => Owner::Guest,
+        };
+        let mut score = 0.0;
+        let mut quadrants = [[false; 4]; 2];
+        for (owner, p1, p2) in harmonie_list {
+            let sign = if owner == own { 1.0 } else { -1.0 };
+            let ((p1_x, p1_y), (p2_x, p2_y)) = (p1.value(), p2.value());
+            let crossing = (p1_y as isize) * (p2_y as isize) < 0 || (p1_x as isize) * (p2_x as isize) < 0;
+            score += sign * if crossing { 3.0 } else { 1.0 };
+            for (x, y) in [(p1_x, p1_y), (p2_x, p2_y)] {
+                if x != 0 && y != 0 {
+                    let quadrant = (x > 0) as usize + 2 * (y > 0) as usize;
+                    quadrants[(owner == own) as usize][quadrant] = true;
+                }
+            }
+        }
+        let coverage = |quadrants: &[bool; 4]| quadrants.iter().filter(|&&covered| covered).count() as f64;
+        score += 0.5 * (coverage(&quadrants[1]) - coverage(&quadrants[0]));
+        1.0 / (1.0 + (-score / 4.0).exp())
+    }
+
+    /// Crossing harmonies of `perspective` minus those of the opponent, they decide the game
+    /// once a reserve is empty.
+    pub fn crossing_margin(&self, harmonie_list: &[(Owner, Position, Position)], perspective: Player) -> isize {
+        let own = match perspective {
+            Player::Host => Owner::Host,
+            Player::Guest => Owner::Guest,
+        };
+        harmonie_list
+            .iter()
+            .filter(|(_, p1, p2)| {
+                let ((p1_x, p1_y), (p2_x, p2_y)) = (p1.value(), p2.value());
+                (p1_y as isize) * (p2_y as isize) < 0 || (p1_x as isize) * (p2_x as isize) < 0
+            })
+            .map(|(owner, _, _)| if *owner == own { 1 } else { -1 })
+            .sum()
+    }
+
+    fn reserve_empty(&self) -> bool {
+        [&self.reserve_guest, &self.reserve_host]
+            .into_iter()
+            .any(|reserve| reserve.iter().all(|(_, count)| *count == 0))
     }
 
     pub fn all_legal_moves(&self, grid: &mut Grid) -> Moves {
@@ -411,16 +466,16 @@ impl Board {
             }
         }
     }
+}
 
-    pub fn next_to_move(&self) -> Player {
-        if self.move_count % 2 == 0 {
-            Player::Guest
-        } else {
-            Player::Host
-        }
+impl Game for Board {
+    type Move = Move;
+
+    fn all_legal_moves(&self) -> Moves {
+        Board::all_legal_moves(self, &mut Grid::create(self))
     }
 
-    pub fn apply_move(&mut self, a_move: Move) {
+    fn apply_move(&mut self, a_move: Move) {
         match a_move {
             Move::Planting(flower_tile, position) => {
                 self.moves_since_planting = 0;
@@ -456,4 +511,188 @@ impl Board {
         }
         self.move_count += 1;
     }
+
+    fn next_to_move(&self) -> Player {
+        if self.move_count % 2 == 0 {
+            Player::Guest
+        } else {
+            Player::Host
+        }
+    }
+
+    fn finished(&self, player: Player) -> Option<Output> {
+        Board::finished(self, Grid::create(self).list_all_harmonies(), player)
+    }
+
+    fn get_random_move(&self, rng: &mut dyn RngCore) -> Option<Move> {
+        Board::get_random_move(self, &Grid::create(self), rng)
+    }
+
+    fn zobrist_hash(&self) -> u64 {
+        const TILE_KEYS: u64 = 2 * 6 * 289;
+        let mut hash = 0;
+        for (owner, played_tiles) in [(0, &self.played_tiles_guest), (1, &self.played_tiles_host)] {
+            for (Tile::Flower(flower), position) in played_tiles {
+                let (x, y) = position.value();
+                let square = (x + 8) as u64 + (y + 8) as u64 * 17;
+                hash ^= zobrist_key((owner * 6 + *flower as u64) * 289 + square);
+            }
+        }
+        //the reserves follow from the played tiles, the counters decide who is next and when the game is drawn
+        hash ^= zobrist_key(TILE_KEYS + self.move_count as u64);
+        hash ^ zobrist_key(TILE_KEYS + (1 << 16) + self.moves_since_planting as u64)
+    }
+
+    fn reward(&self, player: Player, outcome: Output, draw_value: f64) -> f64 {
+        //a game decided by counting crossing harmonies is worth more the clearer the margin, a ring is a full win
+        let harmonies = Grid::create(self).list_all_harmonies();
+        let margin = self.crossing_margin(&harmonies, player);
+        let (guest_ring, host_ring) = Self::rings(&harmonies);
+        let counted = self.reserve_empty() && !guest_ring && !host_ring;
+        match outcome {
+            Output::Win | Output::Loss if counted && (margin > 0) == (outcome == Output::Win) && margin != 0 => {
+                let clarity = margin.unsigned_abs() as f64 / (margin.unsigned_abs() as f64 + 1.0);
+                0.5 + 0.5 * clarity * margin.signum() as f64
+            }
+            _ => outcome.reward(draw_value),
+        }
+    }
+
+    fn moves_left(&self) -> usize {
+        //the game ends with the first empty reserve, roughly every planting comes with an arrangement
+        let reserve_size = |reserve: &Vec<(Tile, u8)>| {
+            reserve
+                .iter()
+                .map(|(_, count)| *count as usize)
+                .sum::<usize>()
+        };
+        2 * reserve_size(&self.reserve_guest).min(reserve_size(&self.reserve_host)) + 1
+    }
+
+    fn evaluate(&self, player: Player) -> f64 {
+        Board::evaluate(self, Grid::create(self).list_all_harmonies(), player)
+    }
+
+    fn move_weight(&self, a_move: &Move) -> f64 {
+        self.move_weights(std::slice::from_ref(a_move))[0]
+    }
+
+    fn move_weights(&self, moves: &[Move]) -> Vec<f64> {
+        //prefer moves that form harmonies
+        let grid = Grid::create(self);
+        moves.iter().map(|a_move| 1.0 + 4.0 * grid.harmonies_formed(a_move) as f64).collect()
+    }
+
+    fn symmetry_count(&self) -> usize {
+        //the first two plantings always go to the south and the north gate
+        if self.move_count < 2 {
+            1
+        } else {
+            SYMMETRIES
+        }
+    }
+
+    fn transformed(&self, symmetry: usize) -> Self {
+        let mut board = self.clone();
+        for (_tile, position) in board.played_tiles_guest.iter_mut().chain(board.played_tiles_host.iter_mut()) {
+            *position = position.transformed(symmetry);
+        }
+        board
+    }
+
+    fn transform_move(&self, a_move: &Move, symmetry: usize) -> Move {
+        match a_move {
+            Move::Planting(flower, position) => Move::Planting(*flower, position.transformed(symmetry)),
+            Move::Arranging(start, end) => Move::Arranging(start.transformed(symmetry), end.transformed(symmetry)),
+        }
+    }
+
+    fn notation(&self) -> Option<String> {
+        //the counters and the tiles of both players as `R@x,y`, the reserves follow from the tiles
+        let tiles = |played_tiles: &Vec<(Tile, Position)>| {
+            if played_tiles.is_empty() {
+                return "-".to_string();
+            }
+            played_tiles
+                .iter()
+                .map(|(Tile::Flower(flower), position)| {
+                    let (x, y) = position.value();
+                    format!("{}@{x},{y}", flower.letter())
+                })
+                .collect::<Vec<_>>()
+                .join(";")
+        };
+        Some(format!(
+            "{} {} {} {}",
+            self.move_count,
+            self.moves_since_planting,
+            tiles(&self.played_tiles_guest),
+            tiles(&self.played_tiles_host)
+        ))
+    }
+
+    fn from_notation(text: &str) -> Option<Self> {
+        let tiles = |text: &str| -> Option<Vec<(Tile, Position)>> {
+            if text == "-" {
+                return Some(Vec::new());
+            }
+            text.split(';')
+                .map(|tile| {
+                    let (letter, position) = tile.split_once('@')?;
+                    let mut letters = letter.chars();
+                    let flower = FlowerTile::from_letter(letters.next()?)?;
+                    if letters.next().is_some() {
+                        return None;
+                    }
+                    let (x, y) = position.split_once(',')?;
+                    let position = Position::new(x.parse().ok()?, y.parse().ok()?)?;
+                    Some((Tile::Flower(flower), position))
+                })
+                .collect()
+        };
+        let fields: Vec<&str> = text.split(' ').collect();
+        let [move_count, moves_since_planting, guest, host] = fields[..] else {
+            return None;
+        };
+        let mut board = Board::empty();
+        board.move_count = move_count.parse().ok()?;
+        board.moves_since_planting = moves_since_planting.parse().ok()?;
+        let count = |reserve: &Vec<(Tile, u8)>, tile: Tile| {
+            reserve
+                .iter()
+                .find(|(reserve_tile, _)| *reserve_tile == tile)
+                .map_or(0, |(_, count)| *count)
+        };
+        for (tile, position) in tiles(guest)? {
+            if count(&board.reserve_guest, tile) == 0 {
+                return None;
+            }
+            board.guest_add_tile(tile, position);
+        }
+        for (tile, position) in tiles(host)? {
+            if count(&board.reserve_host, tile) == 0 {
+                return None;
+            }
+            board.host_add_tile(tile, position);
+        }
+        Some(board)
+    }
+}
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", Grid::create(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monte_carlo_tree_search::check_symmetries;
+
+    #[test]
+    fn moves_follow_the_symmetries() {
+        //the whole game takes too long, the symmetries show up after the first two plantings
+        check_symmetries(Board::empty(), 1, 16);
+    }
 }
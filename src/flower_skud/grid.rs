@@ -18,6 +18,29 @@ impl Grid {
         None
     }
 
+    /// Number of harmonies the tile moved by `a_move` forms at its destination.
+    /// Plantings go to a gate and never form one.
+    pub(super) fn harmonies_formed(&self, a_move: &Move) -> usize {
+        let Move::Arranging(start, end) = a_move else {
+            return 0;
+        };
+        let Some((tile, owner)) = *self.index(start) else {
+            return 0;
+        };
+        [Direction::Up, Direction::Down, Direction::Left, Direction::Right]
+            .into_iter()
+            .filter(|&direction| {
+                let mut found = self.next_tile_in_direction(end, direction);
+                if matches!(&found, Some((_, position, _)) if position == start) {
+                    //the tile leaves its start, so look past it
+                    found = self.next_tile_in_direction(start, direction);
+                }
+                matches!(found, Some((other, position, other_owner))
+                    if other_owner == owner && !position.is_gate() && other.harmonizes(&tile))
+            })
+            .count()
+    }
+
     pub fn create(board: &Board) -> Self {
         let mut grid = Grid {
             cells: vec![None; 289],
@@ -67,8 +90,8 @@ impl Grid {
                 ));
             }
             Move::Arranging(start, end) => {
-                let cell_content = self.index(&start).clone();
-                *self.index_mut(&end) = cell_content.clone();
+                let cell_content = *self.index(&start);
+                *self.index_mut(&end) = cell_content;
                 *self.index_mut(&start) = None;
                 for (index, (_, from, to)) in harmonie_list.clone().into_iter().enumerate().rev() {
                     if from == start || to == start {
@@ -183,7 +206,7 @@ impl Grid {
                 continue ;
             };
             let (last_column_tile_type, last_column_tile_owner) =
-                self.index(last_column_tile_pos).clone().unwrap();
+                (*self.index(last_column_tile_pos)).unwrap();
             let same_owner = *new_column_tile_owner == last_column_tile_owner;
             let tiles_harmonize = last_column_tile_type.harmonizes(new_column_tile_type);
             if same_owner && tiles_harmonize {
@@ -216,7 +239,7 @@ impl Grid {
                 continue ;
             };
             let (last_row_tile_type, last_row_tile_owner): (Tile, Owner) =
-                self.index(last_row_tile_pos).clone().unwrap();
+                (*self.index(last_row_tile_pos)).unwrap();
             let same_owner = *new_row_tile_owner == last_row_tile_owner;
             let tiles_harmonize = last_row_tile_type.harmonizes(new_row_tile_type);
             if same_owner && tiles_harmonize {
@@ -253,9 +276,9 @@ impl std::fmt::Display for Grid {
                             FlowerTile::Lily => "W4",
                             FlowerTile::WhiteJade => "W5",
                         },
-                        match o {
-                            &Owner::Host => "H",
-                            &Owner::Guest => "G",
+                        match *o {
+                            Owner::Host => "H",
+                            Owner::Guest => "G",
                         }
                     )?;
                 } else {
@@ -290,9 +313,9 @@ impl std::fmt::Debug for Grid {
                             FlowerTile::Lily => "W4",
                             FlowerTile::WhiteJade => "W5",
                         },
-                        match o {
-                            &Owner::Host => "H",
-                            &Owner::Guest => "G",
+                        match *o {
+                            Owner::Host => "H",
+                            Owner::Guest => "G",
                         }
                     )?;
                 } else {
@@ -1,4 +1,4 @@
-use crate::monte_carlo_tree_search::{Output, Player};
+use crate::monte_carlo_tree_search::{zobrist_key, Game, Output, Player};
 use std::vec;
 
 mod other;
@@ -13,14 +13,14 @@ fn print_checklist(check_list: &[bool; 289]) {
     for column in -8..=8 {
         print!("  {:2}  ", column);
     }
-    print!("\n");
+    println!();
     for row in (0..17).rev() {
         print!("{:<2}: ", row as isize - 8);
         for column in 0..17 {
             let v = if check_list[column + row * 17] {"t"} else {"f"};
             print!("[  {v} ]");
         }
-        print!("\n");
+        println!();
     }
 }
 
@@ -60,10 +60,9 @@ pub fn all_possibilities_for_piece_to_move(
                         if grid.next_tile_in_direction(&possible_position, Direction::Down).unwrap().0.clashes(&moving_tile_type) {
                             continue 'check_position;
                         }
-                    } else if y < possible_clash_coord.1 {
-                        if grid.next_tile_in_direction(&possible_position, Direction::Up).unwrap().0.clashes(&moving_tile_type) {
-                            continue 'check_position;
-                        }
+                    } else if y < possible_clash_coord.1
+                        && grid.next_tile_in_direction(&possible_position, Direction::Up).unwrap().0.clashes(&moving_tile_type) {
+                        continue 'check_position;
                     }
                 }
                 if y == possible_clash_coord.1 {
@@ -71,10 +70,9 @@ pub fn all_possibilities_for_piece_to_move(
                         if grid.next_tile_in_direction(&possible_position, Direction::Left).unwrap().0.clashes(&moving_tile_type) {
                             continue 'check_position;
                         }
-                    } else if x < possible_clash_coord.0 {
-                        if grid.next_tile_in_direction(&possible_position, Direction::Right).unwrap().0.clashes(&moving_tile_type) {
-                            continue 'check_position;
-                        }
+                    } else if x < possible_clash_coord.0
+                        && grid.next_tile_in_direction(&possible_position, Direction::Right).unwrap().0.clashes(&moving_tile_type) {
+                        continue 'check_position;
                     }
                 }
             }
@@ -139,7 +137,7 @@ fn flood_fill(grid: &Grid, moving_tile_type: Tile, starting_position: &Position)
 
     //flood fill
     for _ in 0..move_range {
-        let move_: Vec<(Position, Direction)> = left_to_check.drain(0..).collect();
+        let move_: Vec<(Position, Direction)> = std::mem::take(&mut left_to_check);
         for (p, d) in move_ {
             let Some(new_pos) = p.add(d) else {
                 continue;
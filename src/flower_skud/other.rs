@@ -25,37 +25,33 @@ impl Direction {
 
 impl Tile {
     pub fn clashes(&self, rhs: &Self) -> bool {
-        match (self, rhs) {
-            (&Tile::Flower(FlowerTile::Rose), &Tile::Flower(FlowerTile::Jasmine)) => true,
-            (&Tile::Flower(FlowerTile::Chrysanthemum), &Tile::Flower(FlowerTile::Lily)) => true,
-            (&Tile::Flower(FlowerTile::Rhododendron), &Tile::Flower(FlowerTile::WhiteJade)) => true,
-            (&Tile::Flower(FlowerTile::Jasmine), &Tile::Flower(FlowerTile::Rose)) => true,
-            (&Tile::Flower(FlowerTile::Lily), &Tile::Flower(FlowerTile::Chrysanthemum)) => true,
-            (&Tile::Flower(FlowerTile::WhiteJade), &Tile::Flower(FlowerTile::Rhododendron)) => true,
-            _ => false,
-        }
+        matches!(
+            (self, rhs),
+            (&Tile::Flower(FlowerTile::Rose), &Tile::Flower(FlowerTile::Jasmine))
+                | (&Tile::Flower(FlowerTile::Chrysanthemum), &Tile::Flower(FlowerTile::Lily))
+                | (&Tile::Flower(FlowerTile::Rhododendron), &Tile::Flower(FlowerTile::WhiteJade))
+                | (&Tile::Flower(FlowerTile::Jasmine), &Tile::Flower(FlowerTile::Rose))
+                | (&Tile::Flower(FlowerTile::Lily), &Tile::Flower(FlowerTile::Chrysanthemum))
+                | (&Tile::Flower(FlowerTile::WhiteJade), &Tile::Flower(FlowerTile::Rhododendron))
+        )
     }
 
     pub fn harmonizes(&self, rhs: &Self) -> bool {
-        match (self, rhs) {
-            (&Tile::Flower(FlowerTile::Rose), &Tile::Flower(FlowerTile::Chrysanthemum)) => true,
-            (&Tile::Flower(FlowerTile::Chrysanthemum), &Tile::Flower(FlowerTile::Rhododendron)) => {
-                true
-            }
-            (&Tile::Flower(FlowerTile::Rhododendron), &Tile::Flower(FlowerTile::Jasmine)) => true,
-            (&Tile::Flower(FlowerTile::Jasmine), &Tile::Flower(FlowerTile::Lily)) => true,
-            (&Tile::Flower(FlowerTile::Lily), &Tile::Flower(FlowerTile::WhiteJade)) => true,
-            (&Tile::Flower(FlowerTile::WhiteJade), &Tile::Flower(FlowerTile::Rose)) => true,
-            (&Tile::Flower(FlowerTile::Chrysanthemum), &Tile::Flower(FlowerTile::Rose)) => true,
-            (&Tile::Flower(FlowerTile::Rhododendron), &Tile::Flower(FlowerTile::Chrysanthemum)) => {
-                true
-            }
-            (&Tile::Flower(FlowerTile::Jasmine), &Tile::Flower(FlowerTile::Rhododendron)) => true,
-            (&Tile::Flower(FlowerTile::Lily), &Tile::Flower(FlowerTile::Jasmine)) => true,
-            (&Tile::Flower(FlowerTile::WhiteJade), &Tile::Flower(FlowerTile::Lily)) => true,
-            (&Tile::Flower(FlowerTile::Rose), &Tile::Flower(FlowerTile::WhiteJade)) => true,
-            _ => false,
-        }
+        matches!(
+            (self, rhs),
+            (&Tile::Flower(FlowerTile::Rose), &Tile::Flower(FlowerTile::Chrysanthemum))
+                | (&Tile::Flower(FlowerTile::Chrysanthemum), &Tile::Flower(FlowerTile::Rhododendron))
+                | (&Tile::Flower(FlowerTile::Rhododendron), &Tile::Flower(FlowerTile::Jasmine))
+                | (&Tile::Flower(FlowerTile::Jasmine), &Tile::Flower(FlowerTile::Lily))
+                | (&Tile::Flower(FlowerTile::Lily), &Tile::Flower(FlowerTile::WhiteJade))
+                | (&Tile::Flower(FlowerTile::WhiteJade), &Tile::Flower(FlowerTile::Rose))
+                | (&Tile::Flower(FlowerTile::Chrysanthemum), &Tile::Flower(FlowerTile::Rose))
+                | (&Tile::Flower(FlowerTile::Rhododendron), &Tile::Flower(FlowerTile::Chrysanthemum))
+                | (&Tile::Flower(FlowerTile::Jasmine), &Tile::Flower(FlowerTile::Rhododendron))
+                | (&Tile::Flower(FlowerTile::Lily), &Tile::Flower(FlowerTile::Jasmine))
+                | (&Tile::Flower(FlowerTile::WhiteJade), &Tile::Flower(FlowerTile::Lily))
+                | (&Tile::Flower(FlowerTile::Rose), &Tile::Flower(FlowerTile::WhiteJade))
+        )
     }
 }
 
@@ -67,6 +63,10 @@ pub enum Move {
 
 pub type Moves = Vec<Move>;
 
+/// The rotation by half a turn and the reflections along both diagonals keep the red and the white
+/// gardens where they are, the other symmetries of the square swap them.
+pub const SYMMETRIES: usize = 4;
+
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
 pub struct Position {
     x: i8,
@@ -80,7 +80,7 @@ impl Position {
                 return true;
             }
         }
-        return false;
+        false
     }
 
     pub const GATES: [Position; 4] = [
@@ -106,6 +106,18 @@ impl Position {
         (self.x, self.y)
     }
 
+    /// The position mapped by one of the [`SYMMETRIES`] of the board, `0` being the identity.
+    pub fn transformed(&self, symmetry: usize) -> Self {
+        let (x, y) = match symmetry {
+            0 => (self.x, self.y),
+            1 => (-self.x, -self.y),
+            2 => (self.y, self.x),
+            3 => (-self.y, -self.x),
+            _ => unreachable!(),
+        };
+        Position { x, y }
+    }
+
     pub fn add(&self, d: Direction) -> Option<Self> {
         match d {
             Direction::Up => Position::new(self.x, self.y + 1),
@@ -130,3 +142,32 @@ pub enum FlowerTile {
     Lily,
     WhiteJade,
 }
+
+impl FlowerTile {
+    const ALL: [FlowerTile; 6] = [
+        FlowerTile::Rose,
+        FlowerTile::Chrysanthemum,
+        FlowerTile::Rhododendron,
+        FlowerTile::Jasmine,
+        FlowerTile::Lily,
+        FlowerTile::WhiteJade,
+    ];
+
+    /// The first letter of the flower's name, used in the notation of boards.
+    pub fn letter(&self) -> char {
+        match self {
+            FlowerTile::Rose => 'R',
+            FlowerTile::Chrysanthemum => 'C',
+            FlowerTile::Rhododendron => 'D',
+            FlowerTile::Jasmine => 'J',
+            FlowerTile::Lily => 'L',
+            FlowerTile::WhiteJade => 'W',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|flower| flower.letter() == letter)
+    }
+}
@@ -1,60 +1,231 @@
-#![allow(unused_imports, unused_imports, dead_code)]
-//mod flower_skud;
+#![allow(unused_imports, dead_code)]
+mod flower_skud;
 //mod hatch_boxes;
 mod monte_carlo_tree_search;
 mod ultimate_tic_tac_toe;
 //mod skud_pai_sho;
-//mod tic_tac_toe;
+mod tic_tac_toe;
 
-use std::borrow::Borrow;
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::time::Duration;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-//use crate::flower_skud::{FlowerTile, Position, Tile};
-//pub use flower_skud::{Board, Grid, Move};
-use monte_carlo_tree_search::{create_root_node, engine, trim_tree, Mode, Node, Origin, Player};
-pub use ultimate_tic_tac_toe::{Board, Move};
+use monte_carlo_tree_search::{
+    advance, create_root_node, engine, export_dot, export_json, load_tree, save_tree, Clock,
+    Config, EpsilonGreedy, Extent, Game, Interval, Mode, NodeBudget, NodeId, Output, Player,
+    Progress, ProgressiveWidening, Rave, Report, Step, StopHandle, Tree, Weighted, WhenFull,
+};
 
 fn main() {
     /*
-    let problem = Board::create_test();
-    let grid = Grid::create(&problem);
+    let problem = flower_skud::Board::create_test();
+    let grid = flower_skud::Grid::create(&problem);
     let _ = problem.finished( grid.list_all_harmonies(),Player::Host);
     */
-    //get a board
-    let board = Board::empty();
-    let mut root = create_root_node(board);
+    //pick the game by its module name, ultimate tic-tac-toe if none is given
+    match std::env::args().nth(1).as_deref() {
+        Some("tic_tac_toe") => play(tic_tac_toe::Board::empty(), Config::default()),
+        //most children only get a handful of visits with that many moves
+        Some("flower_skud") => play(
+            flower_skud::Board::empty(),
+            Config {
+                rave: Some(Rave::default()),
+                widening: Some(ProgressiveWidening::default()),
+                rollout: Arc::new(EpsilonGreedy { epsilon: 0.5 }),
+                rollout_depth: Some(40),
+                ..Config::default()
+            },
+        ),
+        _ => play(
+            ultimate_tic_tac_toe::Board::empty(),
+            Config {
+                rollout: Arc::new(Weighted),
+                node_budget: Some(NodeBudget {
+                    max_nodes: 2_000_000,
+                    when_full: WhenFull::Prune,
+                }),
+                ..Config::default()
+            },
+        ),
+    }
+}
+
+fn play<G: Game>(board: G, mut config: Config<G>) {
+    //the game can be followed by the selection policy, like `uct:0.7` or `puct:1.5:0.5`, `-` keeps the default
+    if let Some(policy) = std::env::args().nth(2).filter(|policy| policy != "-") {
+        match policy.parse() {
+            Ok(policy) => config.selection = policy,
+            Err(error) => {
+                eprintln!("{error}");
+                return;
+            }
+        }
+    }
+    //a short analysis every two seconds of the timed search
+    config.progress = Some(Progress {
+        interval: Interval::Time(Duration::from_secs(2)),
+        line_length: 4,
+        report: Arc::new(|report: &Report<G>| {
+            println!(
+                "{:.0} iterations/s, {} nodes, line {}",
+                report.iterations_per_second,
+                report.nodes,
+                format_line(&report.principal_variation)
+            )
+        }),
+    });
+    //pondering gets its own stop handle, so a line typed during the timed search does not cut it short
+    //and it stays quiet while a move is typed
+    let ponder_config = &Config {
+        stop: StopHandle::default(),
+        progress: None,
+        ..config.clone()
+    };
+    let config = &config;
+    let lines = read_lines(ponder_config.stop.clone());
+    //and then by a file the tree is read from if it exists and written to at the end of input
+    let tree_file = std::env::args().nth(3);
+    let mut tree = match tree_file.as_deref().and_then(|path| File::open(path).ok()) {
+        Some(file) => match load_tree(BufReader::new(file)) {
+            Ok(tree) => {
+                println!("Resuming the saved tree with {} nodes", tree.len());
+                tree
+            }
+            Err(error) => {
+                eprintln!("{error}");
+                return;
+            }
+        },
+        None => create_root_node(board),
+    };
+    let save = |tree: &Tree<G>| {
+        let Some(path) = &tree_file else {
+            return;
+        };
+        let written = File::create(path).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            save_tree(tree, &mut out)?;
+            out.flush()
+        });
+        match written {
+            Ok(()) => println!("Saved the tree to {path}"),
+            Err(error) => eprintln!("Could not save the tree: {error}"),
+        }
+    };
+    //the engine thinks on a clock of its own, the time spent pondering is free
+    let mut clock = Clock {
+        remaining: Duration::from_secs(180),
+        increment: Duration::from_secs(2),
+    };
     loop {
-        //let tree = engine(root, Mode::Iterations(1_000_000));
-        let tree = engine(root, Mode::Time(Duration::from_secs(3)));
-        let root_node = <Rc<RefCell<Node>> as Borrow<RefCell<Node>>>::borrow(&tree).borrow();
-        let sim_count = root_node.simulations;
-        let mut children = root_node.children.clone();
-        children.sort_by_key(|child| (**child).borrow().simulations);
-        for (index, child) in children.iter().enumerate() {
-            let child_node = (**child).borrow();
-            if let Origin::Parent(_, the_move) = &child_node.origin {
-                println!(
-                    "{index:0>3}: [ {:0>7.3} | {:0>7.3} | {:0>7.3}] {:0>7.3}% for {:?}",
-                    child_node.win_count as f64 / child_node.simulations as f64 * 100.0,
-                    child_node.draw_count as f64 / child_node.simulations as f64 * 100.0,
-                    (child_node.simulations - child_node.win_count - child_node.draw_count) as f64
-                        / child_node.simulations as f64
-                        * 100.0,
-                    child_node.simulations as f64 / sim_count as f64 * 100.0,
-                    the_move
-                );
+        //tree = engine(tree, Mode::Iterations(1_000_000), config);
+        let start = Instant::now();
+        let best_move;
+        (tree, best_move) = engine(tree, Mode::Clock(clock), config);
+        let thinking_time = start.elapsed();
+        clock.remaining = clock.remaining.saturating_sub(thinking_time) + clock.increment;
+        println!(
+            "Thought for {:.2}s, {:.0}s left on the clock",
+            thinking_time.as_secs_f64(),
+            clock.remaining.as_secs_f64()
+        );
+        let sim_count = tree.root().simulations();
+        let mut children: Vec<usize> = (0..tree.root().children.len()).collect();
+        children.sort_by_key(|&edge| tree.node(tree.root().children[edge].node).simulations());
+        for (index, &edge_index) in children.iter().enumerate() {
+            let edge = &tree.root().children[edge_index];
+            let child_node = tree.node(edge.node);
+            println!(
+                "{index:0>3}: [ {:0>7.3} | {:0>7.3} | {:0>7.3}] {:.3} {:0>7.3}% for {:?}{}",
+                child_node.win_count() as f64 / child_node.simulations() as f64 * 100.0,
+                child_node.draw_count() as f64 / child_node.simulations() as f64 * 100.0,
+                (child_node
+                    .simulations()
+                    .saturating_sub(child_node.win_count())
+                    .saturating_sub(child_node.draw_count())
+                    .saturating_sub(child_node.estimate_count())) as f64
+                    / child_node.simulations() as f64
+                    * 100.0,
+                child_node.mean_reward(),
+                child_node.simulations() as f64 / sim_count as f64 * 100.0,
+                edge.the_move,
+                proof_note(child_node.proven())
+            );
+            let continuation = tree.variation(edge_index, 5);
+            if continuation.len() > 1 {
+                println!("     then {}", format_line(&continuation[1..]));
             }
         }
         println!("We did {} simulations", sim_count);
+        let principal_variation = tree.principal_variation(8);
+        println!("Expected line: {}", format_line(&principal_variation));
+        if let Some(best_move) = best_move {
+            println!("The engine would play {best_move:?}");
+        }
+        println!(
+            "The tree holds {} nodes in about {:.1} MB",
+            tree.len(),
+            tree.memory_usage() as f64 / 1_000_000.0
+        );
+        if let Some(value) = tree.root().proven() {
+            //the root is proven for the player that moved into it
+            println!("The position is solved{}", proof_note(Some(value.reversed())));
+        }
         //return;
-        println!("Enter a move index to continue with that move:");
-        let index = loop {
-            let mut line = String::new();
-            let _bytes_read = std::io::stdin().read_line(&mut line).unwrap();
-            line.pop();
-            let Ok(input): Result<usize, _> = line.parse() else {
+        let children: Vec<G::Move> = children
+            .iter()
+            .map(|&edge| tree.root().children[edge].the_move.clone())
+            .collect();
+        println!("Enter a move index to continue with that move, `moves` to list all legal moves and `play <index>` to play one of them, or `dot <file>` or `json <file>` to export the tree:");
+        let the_move = loop {
+            //keep searching the current tree until the next line comes in, lines typed during the
+            //timed search stopped a ponder that is not running yet
+            ponder_config.stop.reset();
+            let line = match lines.try_recv() {
+                Ok(line) => line,
+                Err(TryRecvError::Empty) => {
+                    let ponder = Mode::Limits {
+                        iterations: None,
+                        time: None,
+                        nodes: Some(MAX_NODES),
+                    };
+                    tree = engine(tree, ponder, ponder_config).0;
+                    match lines.recv() {
+                        Ok(line) => line,
+                        Err(_) => return save(&tree),
+                    }
+                }
+                Err(TryRecvError::Disconnected) => return save(&tree),
+            };
+            if line.trim() == "moves" {
+                for (index, a_move) in tree.root_board().all_legal_moves().iter().enumerate() {
+                    println!("{index:0>3}: {a_move:?}");
+                }
+                continue;
+            }
+            match line.trim().split_once(' ') {
+                Some((format @ ("dot" | "json"), path)) => {
+                    export(&tree, format, path);
+                    continue;
+                }
+                //any legal move, also one the search never expanded
+                Some(("play", index)) => {
+                    let legal_moves = tree.root_board().all_legal_moves();
+                    let chosen = index.parse::<usize>().ok().and_then(|i| legal_moves.get(i));
+                    match chosen {
+                        Some(a_move) => break a_move.clone(),
+                        None => {
+                            println!("Enter `play` with the index of a move listed by `moves`:");
+                            continue;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            let Ok(input): Result<usize, _> = line.trim().parse() else {
                 println!("Enter a move index (between 0 and {}) to continue with that move:", children.len() - 1);
                 continue;
             };
@@ -65,9 +236,72 @@ fn main() {
                 );
                 continue;
             }
-            break input;
+            break children[input].clone();
         };
-        let selected_node = children[index].clone();
-        root = trim_tree(selected_node);
+        tree = advance(tree, &the_move).expect("the move was taken from the legal moves");
+    }
+}
+
+/// Pondering stops by itself at this tree size, so an idle prompt does not eat all memory.
+const MAX_NODES: usize = 5_000_000;
+
+/// Reads stdin on its own thread and stops `ponder` for every line, the channel closes at the end of input.
+fn read_lines(ponder: StopHandle) -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if sender.send(line).is_err() {
+                break;
+            }
+            ponder.stop();
+        }
+        ponder.stop();
+    });
+    receiver
+}
+
+/// The moves of a line with the win, draw and loss rates of each, for the player making it.
+fn format_line<G: Game>(line: &[Step<G>]) -> String {
+    line.iter()
+        .map(|step| {
+            format!(
+                "{:?} [{:.0}|{:.0}|{:.0}]",
+                step.the_move,
+                step.win_rate * 100.0,
+                step.draw_rate * 100.0,
+                step.loss_rate * 100.0
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Writes the first levels of the tree to `path` for a look at where the search went.
+fn export<G: Game>(tree: &Tree<G>, format: &str, path: &str) {
+    let extent = Extent::Levels(3);
+    let written = File::create(path).and_then(|file| {
+        let mut out = BufWriter::new(file);
+        match format {
+            "dot" => export_dot(tree, extent, &mut out)?,
+            _ => export_json(tree, extent, &mut out)?,
+        }
+        out.flush()
+    });
+    match written {
+        Ok(()) => println!("Exported the tree to {path}"),
+        Err(error) => eprintln!("Could not export the tree: {error}"),
+    }
+}
+
+/// Describes a result proven by the solver, for the player making the move.
+fn proof_note(proven: Option<Output>) -> &'static str {
+    match proven {
+        Some(Output::Win) => ", a forced win",
+        Some(Output::Draw) => ", a forced draw",
+        Some(Output::Loss) => ", a forced loss",
+        None => "",
     }
 }
@@ -0,0 +1,114 @@
+use super::{extract_board, Game, Node, NodeId, Tree, ROOT};
+
+/// One move of a line of play, with the results of the rollouts through it for the player making it.
+#[derive(Clone, Debug)]
+pub struct Step<G: Game> {
+    /// The move on the actual board, even where the tree stores a symmetric version of it.
+    pub the_move: G::Move,
+    pub visits: u32,
+    pub win_rate: f64,
+    pub draw_rate: f64,
+    pub loss_rate: f64,
+}
+
+impl<G: Game> Step<G> {
+    fn new(the_move: G::Move, node: &Node<G>) -> Self {
+        let (win_rate, draw_rate, loss_rate) = node.result_rates();
+        Step {
+            the_move,
+            visits: node.simulations(),
+            win_rate,
+            draw_rate,
+            loss_rate,
+        }
+    }
+}
+
+impl<G: Game> Node<G> {
+    /// The shares of won, drawn and lost rollouts among the simulations, for the player that moved
+    /// into the node. Rollouts cut off with an estimate count as none of them.
+    pub fn result_rates(&self) -> (f64, f64, f64) {
+        // a running search may have counted a simulation but not yet its result
+        let simulations = self.simulations();
+        let (wins, draws) = (self.win_count(), self.draw_count());
+        let losses = simulations
+            .saturating_sub(self.estimate_count())
+            .saturating_sub(wins)
+            .saturating_sub(draws);
+        let simulations = simulations.max(1) as f64;
+        (
+            wins as f64 / simulations,
+            draws as f64 / simulations,
+            losses as f64 / simulations,
+        )
+    }
+}
+
+impl<G: Game> Tree<G> {
+    /// The line the engine expects: the most visited child of the root, then the most visited
+    /// child of that one and so on, for at most `length` moves.
+    pub fn principal_variation(&self, length: usize) -> Vec<Step<G>> {
+        match most_visited(self, ROOT) {
+            Some(edge) => self.variation(edge, length),
+            None => Vec::new(),
+        }
+    }
+
+    /// The lines starting with the `count` most visited moves of the root, best first.
+    pub fn top_lines(&self, count: usize, length: usize) -> Vec<Vec<Step<G>>> {
+        let mut edges: Vec<usize> = (0..self.root().children.len()).collect();
+        edges.sort_by_key(|&edge| std::cmp::Reverse(self.root().children[edge].visits()));
+        edges
+            .into_iter()
+            .take(count)
+            .map(|edge| self.variation(edge, length))
+            .collect()
+    }
+
+    /// The line starting with the root child behind `edge`, followed by the most visited children.
+    pub fn variation(&self, edge: usize, length: usize) -> Vec<Step<G>> {
+        let mut line = Vec::new();
+        let mut real_board = extract_board(self, ROOT);
+        let mut node_board = real_board.clone();
+        let mut current: NodeId = ROOT;
+        let mut next = Some(edge);
+        while let Some(edge) = next.filter(|_| line.len() < length) {
+            let edge = &self.nodes[current].children[edge];
+            let the_move = real_move(&real_board, &node_board, &edge.the_move);
+            line.push(Step::new(the_move.clone(), &self.nodes[edge.node]));
+            real_board.apply_move(the_move);
+            node_board.apply_move(edge.the_move.clone());
+            if edge.symmetry != 0 {
+                node_board = node_board.transformed(edge.symmetry);
+            }
+            current = edge.node;
+            next = most_visited(self, current);
+        }
+        line
+    }
+}
+
+/// The visited child of `id` with the most visits.
+fn most_visited<G: Game>(tree: &Tree<G>, id: NodeId) -> Option<usize> {
+    let children = &tree.nodes[id].children;
+    (0..children.len())
+        .filter(|&edge| children[edge].visits() > 0)
+        .max_by_key(|&edge| children[edge].visits())
+}
+
+/// Maps `node_move`, a move on `node_board` as the tree stores it, to the same move on
+/// `real_board`, of which `node_board` is a symmetric version.
+fn real_move<G: Game>(real_board: &G, node_board: &G, node_move: &G::Move) -> G::Move {
+    let hash = node_board.zobrist_hash();
+    let Some(symmetry) = (0..real_board.symmetry_count())
+        .find(|&symmetry| real_board.transformed(symmetry).zobrist_hash() == hash)
+        .filter(|&symmetry| symmetry != 0)
+    else {
+        return node_move.clone();
+    };
+    real_board
+        .all_legal_moves()
+        .into_iter()
+        .find(|a_move| real_board.transform_move(a_move, symmetry) == *node_move)
+        .unwrap_or_else(|| node_move.clone())
+}
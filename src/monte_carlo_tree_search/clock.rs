@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+/// The game clock of the player to move, searched with [`Mode::Clock`](super::Mode::Clock).
+/// The engine spreads the remaining time over the moves [`Game::moves_left`](super::Game::moves_left)
+/// expects.
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    /// Time left on the clock.
+    pub remaining: Duration,
+    /// Time added to the clock after every move.
+    pub increment: Duration,
+}
+
+impl Clock {
+    /// The soft and the hard time limit for the next move when about `moves_left` moves remain.
+    /// The search may stop after the soft limit and has to stop at the hard one.
+    pub fn limits(&self, moves_left: usize) -> (Duration, Duration) {
+        // a twentieth stays on the clock to get the move sent
+        let usable = self.remaining.saturating_sub(self.remaining / 20);
+        let share = self.remaining / moves_left.max(1) as u32 + self.increment * 3 / 4;
+        let soft = share.min(usable);
+        (soft, (soft * 3).min(usable))
+    }
+}
@@ -0,0 +1,124 @@
+use super::{
+    FinalMove, Game, Progress, ProgressiveWidening, Rave, RolloutPolicy, SelectionPolicy,
+    StopHandle, Uniform,
+};
+use std::sync::Arc;
+use std::thread;
+
+/// Settings for a search, passed along to [`engine`](super::engine).
+#[derive(Clone)]
+pub struct Config<G: Game> {
+    pub board_cache: BoardCache,
+    pub parallelism: Parallelism,
+    /// Number of threads the search is spread over, zero plays the rollouts on the searching thread.
+    /// With [`Parallelism::Leaf`] every expansion adds this many children at once to keep them busy.
+    pub workers: usize,
+    /// Share one node between all move orders that lead to the same position.
+    pub transpositions: bool,
+    /// Treat positions that are symmetric to each other as the same, both for the moves of a node
+    /// and in the transposition table.
+    pub symmetries: bool,
+    pub selection: SelectionPolicy,
+    /// What a draw is worth to the selection, between a loss at zero and a win at one.
+    pub draw_value: f64,
+    /// Prove wins, losses and draws from the end of the game upwards and stop searching solved subtrees.
+    pub solver: bool,
+    /// Limit the children of a node by its visits instead of expanding all its moves.
+    pub widening: Option<ProgressiveWidening>,
+    /// Blend the value of a child with its all-moves-as-first statistics.
+    pub rave: Option<Rave>,
+    /// Picks the moves of the rollouts.
+    pub rollout: Arc<dyn RolloutPolicy<G>>,
+    /// Cut the rollouts off after this many moves and let [`Game::evaluate`] guess the result.
+    pub rollout_depth: Option<usize>,
+    /// How the engine picks its move once the search is over.
+    pub final_move: FinalMove,
+    /// Draw the final move at random, better scoring moves being more likely the lower it is.
+    /// Zero or below plays the best move.
+    pub temperature: Option<f64>,
+    /// Caps the number of nodes in the tree.
+    pub node_budget: Option<NodeBudget>,
+    /// Ends the search early when stopped from another thread.
+    pub stop: StopHandle,
+    /// Seeds all random choices of a search, a fresh seed is drawn for every search if `None`.
+    /// The same seed and iteration budget give the same tree with [`Parallelism::Leaf`] and
+    /// [`Parallelism::Root`], with [`Parallelism::Tree`] the threads still race each other.
+    pub seed: Option<u64>,
+    /// Reports on the search while it runs.
+    pub progress: Option<Progress<G>>,
+}
+
+impl<G: Game> Default for Config<G> {
+    fn default() -> Self {
+        Config {
+            board_cache: BoardCache::Incremental,
+            parallelism: Parallelism::Leaf,
+            workers: thread::available_parallelism().map_or(1, |n| n.get()),
+            transpositions: true,
+            symmetries: true,
+            selection: SelectionPolicy::default(),
+            draw_value: 0.5,
+            solver: true,
+            widening: None,
+            rave: None,
+            rollout: Arc::new(Uniform),
+            rollout_depth: None,
+            final_move: FinalMove::default(),
+            temperature: None,
+            node_budget: None,
+            stop: StopHandle::default(),
+            seed: None,
+            progress: None,
+        }
+    }
+}
+
+/// How the engine gets hold of the board that belongs to a node.
+#[derive(Clone, Copy, Debug)]
+pub enum BoardCache {
+    /// Every node keeps a copy of its board.
+    Full,
+    /// Only nodes whose depth is a multiple of the given ply count keep their board,
+    /// the others replay the moves from the closest one above them.
+    EveryNth(usize),
+    /// No node keeps a board, the moves are applied one by one while descending from the root.
+    Incremental,
+}
+
+impl BoardCache {
+    pub(super) fn keeps_board_at(self, depth: usize) -> bool {
+        match self {
+            BoardCache::Full => true,
+            BoardCache::EveryNth(plies) => depth.is_multiple_of(plies),
+            BoardCache::Incremental => false,
+        }
+    }
+}
+
+/// Which part of the search is spread over the `workers` threads.
+#[derive(Clone, Copy, Debug)]
+pub enum Parallelism {
+    /// One thread walks the tree, the rollouts of each expansion run on the worker pool.
+    Leaf,
+    /// All workers walk the same tree at once, each doing its own rollouts.
+    Tree,
+    /// Every worker searches its own tree, the root children are merged by move at the end.
+    Root,
+}
+
+/// The most nodes a tree may hold and what the search does once it is full.
+/// With [`Parallelism::Root`] every tree gets the whole budget.
+#[derive(Clone, Copy, Debug)]
+pub struct NodeBudget {
+    pub max_nodes: usize,
+    pub when_full: WhenFull,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum WhenFull {
+    /// Keep searching the nodes there are without adding new ones.
+    StopExpanding,
+    /// Cut the least visited subtrees off until the tree is down to three quarters of the budget.
+    /// Their moves are expanded again when the search comes back to them.
+    Prune,
+}
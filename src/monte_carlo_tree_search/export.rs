@@ -0,0 +1,138 @@
+use super::{Edge, Game, NodeId, Tree, ROOT};
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, Write};
+
+/// Which nodes an export of the tree shows, the root is always part of it.
+#[derive(Clone, Copy, Debug)]
+pub enum Extent {
+    /// The nodes at most this many moves below the root.
+    Levels(usize),
+    /// The nodes with at least this many visits, as far as they hang below each other.
+    MinVisits(u32),
+}
+
+/// Writes the part of `tree` given by `extent` as a Graphviz digraph. Nodes are labelled with the
+/// move leading to them, their visits and the win, draw and loss rates for the player making that
+/// move, edges with their move and visits. Moves are the ones stored in the tree, so they can be
+/// symmetric versions of the moves on the actual board.
+pub fn export_dot<G: Game>(tree: &Tree<G>, extent: Extent, out: &mut impl Write) -> io::Result<()> {
+    let part = Part::new(tree, extent);
+    writeln!(out, "digraph tree {{")?;
+    writeln!(out, "    node [shape=box];")?;
+    for &(id, the_move) in &part.nodes {
+        let node = &tree.nodes[id];
+        let (win_rate, draw_rate, loss_rate) = node.result_rates();
+        let title = match the_move {
+            Some(the_move) => format!("{the_move:?}"),
+            None => "root".to_string(),
+        };
+        writeln!(
+            out,
+            "    n{id} [label=\"{}\\n{} visits\\nW {:.1}% D {:.1}% L {:.1}%\"];",
+            escape(&title),
+            node.simulations(),
+            win_rate * 100.0,
+            draw_rate * 100.0,
+            loss_rate * 100.0
+        )?;
+    }
+    for (parent, edge) in &part.edges {
+        writeln!(
+            out,
+            "    n{parent} -> n{} [label=\"{}\\n{}\"];",
+            edge.node,
+            escape(&format!("{:?}", edge.the_move)),
+            edge.visits()
+        )?;
+    }
+    writeln!(out, "}}")
+}
+
+/// Writes the part of `tree` given by `extent` as JSON, an object with a list of `nodes` and a list
+/// of `edges` between them carrying the same information as [`export_dot`].
+pub fn export_json<G: Game>(
+    tree: &Tree<G>,
+    extent: Extent,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let part = Part::new(tree, extent);
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"nodes\": [")?;
+    for (index, &(id, the_move)) in part.nodes.iter().enumerate() {
+        let node = &tree.nodes[id];
+        let (win_rate, draw_rate, loss_rate) = node.result_rates();
+        let the_move = match the_move {
+            Some(the_move) => format!("\"{}\"", escape(&format!("{the_move:?}"))),
+            None => "null".to_string(),
+        };
+        writeln!(
+            out,
+            "    {{\"id\": {id}, \"move\": {the_move}, \"visits\": {}, \"win_rate\": {}, \
+             \"draw_rate\": {}, \"loss_rate\": {}}}{}",
+            node.simulations(),
+            win_rate,
+            draw_rate,
+            loss_rate,
+            separator(index, part.nodes.len())
+        )?;
+    }
+    writeln!(out, "  ],")?;
+    writeln!(out, "  \"edges\": [")?;
+    for (index, (parent, edge)) in part.edges.iter().enumerate() {
+        writeln!(
+            out,
+            "    {{\"from\": {parent}, \"to\": {}, \"move\": \"{}\", \"visits\": {}}}{}",
+            edge.node,
+            escape(&format!("{:?}", edge.the_move)),
+            edge.visits(),
+            separator(index, part.edges.len())
+        )?;
+    }
+    writeln!(out, "  ]")?;
+    writeln!(out, "}}")
+}
+
+/// The nodes of an export with the move they were first reached by, and the edges between them.
+struct Part<'a, G: Game> {
+    nodes: Vec<(NodeId, Option<&'a G::Move>)>,
+    edges: Vec<(NodeId, &'a Edge<G>)>,
+}
+
+impl<'a, G: Game> Part<'a, G> {
+    fn new(tree: &'a Tree<G>, extent: Extent) -> Self {
+        let mut nodes = vec![(ROOT, None)];
+        let mut edges = Vec::new();
+        let mut seen = HashSet::from([ROOT]);
+        let mut queue = VecDeque::from([(ROOT, 0)]);
+        while let Some((id, depth)) = queue.pop_front() {
+            for edge in &tree.nodes[id].children {
+                let child = &tree.nodes[edge.node];
+                let shown = match extent {
+                    Extent::Levels(levels) => depth < levels,
+                    Extent::MinVisits(visits) => child.simulations() >= visits,
+                };
+                if !shown {
+                    continue;
+                }
+                edges.push((id, edge));
+                if seen.insert(edge.node) {
+                    nodes.push((edge.node, Some(&edge.the_move)));
+                    queue.push_back((edge.node, depth + 1));
+                }
+            }
+        }
+        Part { nodes, edges }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn separator(index: usize, count: usize) -> &'static str {
+    if index + 1 < count {
+        ","
+    } else {
+        ""
+    }
+}
@@ -0,0 +1,102 @@
+use super::{Config, Edge, Game, Output, Tree};
+use rand::{Rng, RngCore};
+
+/// How the engine picks its move among the root children once the search is over.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum FinalMove {
+    /// The child searched the most, the usual choice.
+    #[default]
+    MostVisits,
+    /// The child with the best average reward, however few visits it got.
+    HighestValue,
+    /// The child with the best lower confidence bound, `mean - confidence / sqrt(visits)`.
+    SecureChild { confidence: f64 },
+    /// The child closest to being both the most visited and the best valued one.
+    MaxRobust,
+}
+
+impl FinalMove {
+    /// Scores the visited `edges`, higher is better and the best one scores one.
+    fn scores<G: Game>(&self, tree: &Tree<G>, edges: &[&Edge<G>]) -> Vec<f64> {
+        let visits: Vec<f64> = edges.iter().map(|edge| edge.visits() as f64).collect();
+        let means: Vec<f64> = edges
+            .iter()
+            .map(|edge| tree.node(edge.node).mean_reward())
+            .collect();
+        let relative = |values: Vec<f64>| {
+            let best = values.iter().copied().fold(f64::MIN_POSITIVE, f64::max);
+            values.into_iter().map(|value| value / best).collect()
+        };
+        match *self {
+            FinalMove::MostVisits => relative(visits),
+            FinalMove::HighestValue => relative(means),
+            FinalMove::SecureChild { confidence } => {
+                // the bounds can be negative, so they are shifted instead of divided
+                let bounds: Vec<f64> = means
+                    .iter()
+                    .zip(&visits)
+                    .map(|(mean, visits)| mean - confidence / visits.sqrt())
+                    .collect();
+                let best = bounds.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                bounds.into_iter().map(|bound| 1.0 + bound - best).collect()
+            }
+            FinalMove::MaxRobust => relative(visits)
+                .into_iter()
+                .zip(relative(means))
+                .map(|(visits, mean): (f64, f64)| visits.min(mean))
+                .collect(),
+        }
+    }
+}
+
+/// The root child the engine plays under `config.final_move`, `None` before anything was searched.
+/// A proven win is always taken and a proven loss only if nothing else is left. With a positive
+/// `config.temperature` the child is drawn with `rng` with a probability of `exp(score / temperature)`,
+/// a temperature of zero or below picks the best child like no temperature.
+pub fn best_child<'a, G: Game>(
+    tree: &'a Tree<G>,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+) -> Option<&'a Edge<G>> {
+    let children = &tree.root().children;
+    let proven = |edge: &Edge<G>| tree.node(edge.node).proven();
+    if let Some(win) = children
+        .iter()
+        .find(|edge| proven(edge) == Some(Output::Win))
+    {
+        return Some(win);
+    }
+    let mut edges: Vec<&Edge<G>> = children
+        .iter()
+        .filter(|edge| edge.visits() > 0 && proven(edge) != Some(Output::Loss))
+        .collect();
+    if edges.is_empty() {
+        edges = children.iter().filter(|edge| edge.visits() > 0).collect();
+    }
+    if edges.is_empty() {
+        return None;
+    }
+    let scores = config.final_move.scores(tree, &edges);
+    let index = match config.temperature {
+        Some(temperature) if temperature > 0.0 => {
+            // shifted by the best score so the largest weight is one and nothing overflows
+            let best = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let weights: Vec<f64> = scores
+                .iter()
+                .map(|score| ((score - best) / temperature).exp())
+                .collect();
+            let mut remaining = rng.gen_range(0.0..weights.iter().sum::<f64>());
+            weights
+                .iter()
+                .position(|weight| {
+                    remaining -= weight;
+                    remaining < 0.0
+                })
+                .unwrap_or(weights.len() - 1)
+        }
+        _ => (0..scores.len())
+            .max_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+            .unwrap(),
+    };
+    Some(edges[index])
+}
@@ -0,0 +1,149 @@
+use super::{Output, Player};
+use rand::RngCore;
+use std::fmt::{Debug, Display};
+
+/// Everything the engine needs to know about a two player game to search it.
+pub trait Game: Clone + Display + Send + Sync + 'static {
+    type Move: Clone + PartialEq + Debug + Send + Sync + 'static;
+
+    /// All moves the player to move can make. Empty once the game is finished.
+    fn all_legal_moves(&self) -> Vec<Self::Move>;
+
+    fn apply_move(&mut self, a_move: Self::Move);
+
+    fn next_to_move(&self) -> Player;
+
+    /// The result of the game from the point of view of `player`, `None` while it is still running.
+    fn finished(&self, player: Player) -> Option<Output>;
+
+    /// A random legal move drawn with `rng`, used to play out the rollouts.
+    fn get_random_move(&self, rng: &mut dyn RngCore) -> Option<Self::Move>;
+
+    /// Hash of the position, the same no matter in which order the moves leading to it were played.
+    /// Built by xor-ing [`zobrist_key`]s for everything on the board.
+    fn zobrist_hash(&self) -> u64;
+
+    /// A rough guess at how many moves the player to move still has to make,
+    /// [`Mode::Clock`](super::Mode::Clock) spreads the remaining time over them.
+    fn moves_left(&self) -> usize {
+        20
+    }
+
+    /// The reward of the finished game for `player`, between a loss at zero and a win at one.
+    /// `outcome` is what [`Game::finished`] returned, games with a margin of victory can grade it.
+    fn reward(&self, _player: Player, outcome: Output, draw_value: f64) -> f64 {
+        outcome.reward(draw_value)
+    }
+
+    /// A guess at the chance that `player` wins from here, a draw counting as half a win.
+    /// Ends the rollouts that are cut off by [`Config::rollout_depth`](super::Config::rollout_depth).
+    fn evaluate(&self, _player: Player) -> f64 {
+        0.5
+    }
+
+    /// How promising `a_move` looks before it is searched, relative to the other legal moves.
+    /// Normalised over all legal moves it is the prior of the move, the weighted rollout
+    /// policies play moves by it too.
+    fn move_weight(&self, _a_move: &Self::Move) -> f64 {
+        1.0
+    }
+
+    /// [`Game::move_weight`] for several moves at once, for games that can share work between them.
+    fn move_weights(&self, moves: &[Self::Move]) -> Vec<f64> {
+        moves
+            .iter()
+            .map(|a_move| self.move_weight(a_move))
+            .collect()
+    }
+
+    /// The position as one line of text, for saving trees. `None` for games that cannot be saved.
+    fn notation(&self) -> Option<String> {
+        None
+    }
+
+    /// Reads a position written by [`Game::notation`].
+    fn from_notation(_text: &str) -> Option<Self> {
+        None
+    }
+
+    /// A move of this position as text for saved trees, without line breaks. It is read back by
+    /// looking it up among the notations of the legal moves.
+    fn move_notation(&self, a_move: &Self::Move) -> String {
+        format!("{a_move:?}")
+    }
+
+    /// Number of symmetries of the position, symmetry `0` is always the identity.
+    fn symmetry_count(&self) -> usize {
+        1
+    }
+
+    /// The position mapped by `symmetry`.
+    fn transformed(&self, _symmetry: usize) -> Self {
+        self.clone()
+    }
+
+    /// A move of this position mapped by `symmetry`, so that it is the same move in [`Game::transformed`].
+    fn transform_move(&self, a_move: &Self::Move, _symmetry: usize) -> Self::Move {
+        a_move.clone()
+    }
+
+    /// The symmetry undoing `symmetry`. By default every symmetry undoes itself, which fits
+    /// reflections and half turns but not quarter turns.
+    fn inverse_symmetry(&self, symmetry: usize) -> usize {
+        symmetry
+    }
+
+    /// The canonical form of the position, the symmetric version with the smallest hash,
+    /// together with the symmetry mapping this position onto it.
+    fn canonical(&self) -> (Self, usize) {
+        (0..self.symmetry_count())
+            .map(|symmetry| (self.transformed(symmetry), symmetry))
+            .min_by_key(|(board, _)| board.zobrist_hash())
+            .expect("symmetry 0 always exists")
+    }
+}
+
+/// A fixed pseudo random number for every `index`, the building block of the Zobrist hashes.
+pub const fn zobrist_key(index: u64) -> u64 {
+    //splitmix64
+    let mut z = index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Plays `games` random games from `board` for at most `max_plies` moves each and checks on the
+/// way that every legal move commutes with every symmetry and that the inverse symmetries undo
+/// them.
+#[cfg(test)]
+pub fn check_symmetries<G: Game>(board: G, games: usize, max_plies: usize) {
+    use rand::{rngs::StdRng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(8);
+    for _ in 0..games {
+        let mut board = board.clone();
+        for _ in 0..max_plies {
+            if board.finished(board.next_to_move()).is_some() {
+                break;
+            }
+            for symmetry in 0..board.symmetry_count() {
+                let transformed = board.transformed(symmetry);
+                let back = transformed.transformed(board.inverse_symmetry(symmetry));
+                assert_eq!(back.zobrist_hash(), board.zobrist_hash());
+                for a_move in board.all_legal_moves() {
+                    let mut played = board.clone();
+                    played.apply_move(a_move.clone());
+                    let mut moved = transformed.clone();
+                    moved.apply_move(board.transform_move(&a_move, symmetry));
+                    assert_eq!(
+                        played.transformed(symmetry).zobrist_hash(),
+                        moved.zobrist_hash()
+                    );
+                }
+            }
+            let Some(a_move) = board.get_random_move(&mut rng) else {
+                break;
+            };
+            board.apply_move(a_move);
+        }
+    }
+}
@@ -0,0 +1,1323 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, RngCore, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+use std::ops::Add;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod analysis;
+pub use analysis::*;
+mod clock;
+pub use clock::*;
+mod config;
+pub use config::*;
+mod export;
+pub use export::*;
+mod final_move;
+pub use final_move::*;
+mod game;
+pub use game::*;
+mod parallel;
+mod pool;
+use pool::WorkerPool;
+mod progress;
+pub use progress::*;
+mod rollout;
+pub use rollout::*;
+mod selection;
+pub use selection::*;
+mod storage;
+pub use storage::*;
+
+/// Index of a node inside the arena of its [`Tree`].
+pub type NodeId = usize;
+
+/// The root always sits at the start of the arena.
+pub const ROOT: NodeId = 0;
+
+type Moves<G> = Vec<<G as Game>::Move>;
+
+/// The search tree. All nodes live in one arena and point at each other by index.
+///
+/// With transpositions enabled a position reached by different move orders is only stored once,
+/// which turns the tree into a directed acyclic graph.
+pub struct Tree<G: Game> {
+    nodes: Vec<Node<G>>,
+    /// The node for each position hash, filled when transpositions are enabled.
+    table: HashMap<u64, NodeId>,
+}
+
+/// The counters are atomic so several threads can update them while walking the same tree.
+pub struct Node<G: Game> {
+    simulations: AtomicU32,
+    win_count: AtomicU32,
+    draw_count: AtomicU32,
+    /// Rollouts cut off before the end of the game, they are neither wins, draws nor losses.
+    estimate_count: AtomicU32,
+    /// Sum of the rewards of all rollouts and of their squares, stored as `f64` bits.
+    reward_sum: AtomicU64,
+    reward_squares: AtomicU64,
+    virtual_loss: AtomicU32,
+    /// The proven value encoded by [`encode_proof`], zero while unknown.
+    proven: AtomicU8,
+    possible_moves: Moves<G>,
+    pub children: Vec<Edge<G>>,
+    /// The first parent the node was reached from, used to rebuild its board.
+    pub origin: Origin<G>,
+    depth: usize,
+    board: Option<G>,
+    hash: u64,
+    /// Sum of the move weights of all moves, to turn the weight of a move into its prior.
+    /// Only filled when the selection policy uses priors.
+    total_weight: f64,
+}
+
+pub enum Origin<G: Game> {
+    /// The parent, the move played there and the symmetry mapping the resulting board onto the
+    /// one of this node.
+    Parent(NodeId, G::Move, usize),
+    Root(G),
+}
+
+/// The link from a node to one of its children.
+pub struct Edge<G: Game> {
+    pub the_move: G::Move,
+    pub node: NodeId,
+    /// How often the search went through this edge, which differs from the visits of the
+    /// child once it has several parents.
+    visits: AtomicU32,
+    /// The symmetry mapping the board after `the_move` onto the board of `node`, which is only
+    /// not the identity if the child was found in the transposition table as a symmetric position.
+    pub symmetry: usize,
+    /// The prior probability of `the_move` being the best move, one when priors are not used.
+    pub prior: f64,
+    /// All-moves-as-first statistics: rollouts through the parent in which the player to move
+    /// played `the_move` at some point, and how they ended for that player.
+    amaf_visits: AtomicU32,
+    /// Sum of the values, stored as `f64` bits.
+    amaf_value: AtomicU64,
+}
+
+impl<G: Game> Edge<G> {
+    fn new(the_move: G::Move, node: NodeId, symmetry: usize, prior: f64) -> Self {
+        Edge {
+            the_move,
+            node,
+            visits: AtomicU32::new(0),
+            symmetry,
+            prior,
+            amaf_visits: AtomicU32::new(0),
+            amaf_value: AtomicU64::new(0.0f64.to_bits()),
+        }
+    }
+
+    fn add_amaf(&self, value: f64) {
+        self.amaf_visits.fetch_add(1, Ordering::Relaxed);
+        add_f64(&self.amaf_value, value);
+    }
+
+    fn add_amaf_statistics(&self, other: &Edge<G>) {
+        self.amaf_visits
+            .fetch_add(other.amaf_visits.load(Ordering::Relaxed), Ordering::Relaxed);
+        add_f64(&self.amaf_value, load_f64(&other.amaf_value));
+    }
+
+    pub fn visits(&self) -> u32 {
+        self.visits.load(Ordering::Relaxed)
+    }
+}
+
+impl<G: Game> Node<G> {
+    fn new(board: &G, origin: Origin<G>, depth: usize, config: &Config<G>) -> Self {
+        let possible_moves = if config.symmetries {
+            distinct_moves(board, board.all_legal_moves(), Vec::new())
+        } else {
+            board.all_legal_moves()
+        };
+        let proven = if config.solver && possible_moves.is_empty() {
+            let mover = board.next_to_move().opponent();
+            encode_proof(Some(board.finished(mover).unwrap_or(Output::Draw)))
+        } else {
+            encode_proof(None)
+        };
+        let total_weight = if config.selection.uses_priors() {
+            total_weight(board, &possible_moves)
+        } else {
+            0.0
+        };
+        Node {
+            simulations: AtomicU32::new(0),
+            win_count: AtomicU32::new(0),
+            draw_count: AtomicU32::new(0),
+            estimate_count: AtomicU32::new(0),
+            reward_sum: AtomicU64::new(0.0f64.to_bits()),
+            reward_squares: AtomicU64::new(0.0f64.to_bits()),
+            virtual_loss: AtomicU32::new(0),
+            proven: AtomicU8::new(proven),
+            possible_moves,
+            children: Vec::new(),
+            origin,
+            depth,
+            board: config
+                .board_cache
+                .keeps_board_at(depth)
+                .then(|| board.clone()),
+            hash: board.zobrist_hash(),
+            total_weight,
+        }
+    }
+
+    pub fn simulations(&self) -> u32 {
+        self.simulations.load(Ordering::Relaxed)
+    }
+
+    /// Wins for the player that made the move leading to this node.
+    pub fn win_count(&self) -> u32 {
+        self.win_count.load(Ordering::Relaxed)
+    }
+
+    pub fn draw_count(&self) -> u32 {
+        self.draw_count.load(Ordering::Relaxed)
+    }
+
+    /// Rollouts that were cut off and evaluated instead of played to the end.
+    pub fn estimate_count(&self) -> u32 {
+        self.estimate_count.load(Ordering::Relaxed)
+    }
+
+    /// Sum of the rewards of all rollouts, for the player that moved into this node.
+    pub fn reward_sum(&self) -> f64 {
+        load_f64(&self.reward_sum)
+    }
+
+    /// The average reward, between a loss at zero and a win at one.
+    pub fn mean_reward(&self) -> f64 {
+        self.reward_sum() / self.simulations().max(1) as f64
+    }
+
+    /// The game theoretic value for the player that made the move leading to this node, once the
+    /// solver has proven it.
+    pub fn proven(&self) -> Option<Output> {
+        decode_proof(self.proven.load(Ordering::Relaxed))
+    }
+
+    fn prove(&self, value: Output) {
+        self.proven
+            .store(encode_proof(Some(value)), Ordering::Relaxed);
+    }
+
+    /// Whether the node has moves left to expand and progressive widening lets it have another child.
+    fn wants_expansion(&self, config: &Config<G>) -> bool {
+        !self.possible_moves.is_empty()
+            && config.widening.is_none_or(|widening| {
+                self.children.len() < widening.allowed_children(self.simulations())
+            })
+    }
+
+    /// Visits counted as losses for searches that are still running through this node.
+    fn virtual_loss(&self) -> u32 {
+        self.virtual_loss.load(Ordering::Relaxed)
+    }
+
+    fn add_statistics(&self, other: &Node<G>) {
+        self.simulations
+            .fetch_add(other.simulations(), Ordering::Relaxed);
+        self.win_count
+            .fetch_add(other.win_count(), Ordering::Relaxed);
+        self.draw_count
+            .fetch_add(other.draw_count(), Ordering::Relaxed);
+        self.estimate_count
+            .fetch_add(other.estimate_count(), Ordering::Relaxed);
+        add_f64(&self.reward_sum, other.reward_sum());
+        add_f64(&self.reward_squares, load_f64(&other.reward_squares));
+    }
+}
+
+impl<G: Game> Tree<G> {
+    pub fn root(&self) -> &Node<G> {
+        &self.nodes[ROOT]
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node<G> {
+        &self.nodes[id]
+    }
+
+    /// The position at the root, the moves of its edges are moves on this board.
+    pub fn root_board(&self) -> &G {
+        match &self.root().origin {
+            Origin::Root(board) => board,
+            Origin::Parent(..) => unreachable!("the root has no parent"),
+        }
+    }
+
+    /// Whether the solver has proven the value of the root, which ends the search.
+    pub fn solved(&self) -> bool {
+        self.root().proven().is_some()
+    }
+
+    /// Number of nodes in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Estimated bytes taken by the tree, without what boards and moves keep on the heap themselves.
+    pub fn memory_usage(&self) -> usize {
+        let per_node: usize = self
+            .nodes
+            .iter()
+            .map(|node| {
+                node.possible_moves.capacity() * size_of::<G::Move>()
+                    + node.children.capacity() * size_of::<Edge<G>>()
+                    + node.board.as_ref().map_or(0, |_| size_of::<G>())
+            })
+            .sum();
+        size_of::<Tree<G>>()
+            + self.nodes.capacity() * size_of::<Node<G>>()
+            + per_node
+            + self.table.capacity() * (size_of::<(u64, NodeId)>() + 1)
+    }
+
+    /// Whether the tree holds as many nodes as the node budget allows.
+    fn is_full(&self, config: &Config<G>) -> bool {
+        config
+            .node_budget
+            .is_some_and(|budget| self.nodes.len() >= budget.max_nodes)
+    }
+
+    /// Prunes a full tree if the node budget asks for it.
+    /// Returns false if that did not make room, the search then goes on without expanding.
+    fn make_room(&mut self, config: &Config<G>) -> bool {
+        let Some(NodeBudget {
+            max_nodes,
+            when_full: WhenFull::Prune,
+        }) = config.node_budget
+        else {
+            return false;
+        };
+        self.prune(max_nodes / 4 * 3);
+        !self.is_full(config)
+    }
+
+    /// Cuts the least visited subtrees off until about `target` nodes are left, their moves go back
+    /// to the moves left to expand. Proven children are kept for the solver.
+    fn prune(&mut self, target: usize) {
+        let excess = self.nodes.len().saturating_sub(target);
+        // the visits of a child are about the size of its subtree, one rollout per expansion
+        let mut candidates: Vec<(u32, NodeId, usize)> = Vec::new();
+        for (parent, node) in self.nodes.iter().enumerate() {
+            for (index, edge) in node.children.iter().enumerate() {
+                let child = &self.nodes[edge.node];
+                if child.proven().is_none() {
+                    candidates.push((child.simulations(), parent, index));
+                }
+            }
+        }
+        candidates.sort_unstable();
+        let mut freed = 0;
+        let mut cuts: Vec<(NodeId, usize)> = Vec::new();
+        for (visits, parent, index) in candidates {
+            if freed >= excess {
+                break;
+            }
+            freed += visits.max(1) as usize;
+            cuts.push((parent, index));
+        }
+        // from the last edge backwards, so the remaining indices stay valid
+        cuts.sort_unstable_by(|a, b| b.cmp(a));
+        for (parent, index) in cuts {
+            let edge = self.nodes[parent].children.remove(index);
+            self.nodes[parent].possible_moves.push(edge.the_move);
+        }
+        let board = extract_board(self, ROOT);
+        let tree = std::mem::replace(
+            self,
+            Tree {
+                nodes: Vec::new(),
+                table: HashMap::new(),
+            },
+        );
+        *self = tree.compact(ROOT, board);
+    }
+
+    fn push(&mut self, node: Node<G>) -> NodeId {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Links `parent`, whose position is `parent_board`, to the position reached by `the_move`.
+    /// Returns the index of the new edge and the board of the child.
+    /// The child is looked up in the transposition table first and only created if it is not known yet.
+    /// With symmetries enabled the table is keyed by the canonical form, so the child found may be
+    /// a symmetric version of the board, the board returned is then transformed to match it.
+    fn add_child(
+        &mut self,
+        parent: NodeId,
+        parent_board: &G,
+        the_move: G::Move,
+        config: &Config<G>,
+        rng: &mut dyn RngCore,
+    ) -> (usize, G) {
+        let prior = if config.selection.uses_priors() {
+            parent_board.move_weight(&the_move) / self.nodes[parent].total_weight
+        } else {
+            1.0
+        };
+        let mut board = parent_board.clone();
+        board.apply_move(the_move.clone());
+        let key = if config.symmetries {
+            board.canonical().0.zobrist_hash()
+        } else {
+            board.zobrist_hash()
+        };
+        let known_node = if config.transpositions {
+            self.table.get(&key).copied()
+        } else {
+            None
+        };
+        let (child, symmetry) = match known_node {
+            Some(child) => {
+                let hash = self.nodes[child].hash;
+                let symmetry = (0..board.symmetry_count())
+                    .find(|&symmetry| board.transformed(symmetry).zobrist_hash() == hash)
+                    .unwrap_or(0);
+                (child, symmetry)
+            }
+            None => {
+                let depth = self.nodes[parent].depth + 1;
+                let mut node = Node::new(
+                    &board,
+                    Origin::Parent(parent, the_move.clone(), 0),
+                    depth,
+                    config,
+                );
+                if config.widening.is_some() {
+                    node.possible_moves =
+                        unlock_order(&board, std::mem::take(&mut node.possible_moves), rng);
+                }
+                let child = self.push(node);
+                if config.transpositions {
+                    self.table.insert(key, child);
+                }
+                (child, 0)
+            }
+        };
+        let children = &mut self.nodes[parent].children;
+        children.push(Edge::new(the_move, child, symmetry, prior));
+        if symmetry != 0 {
+            board = board.transformed(symmetry);
+        }
+        (children.len() - 1, board)
+    }
+
+    /// Brings a root that was created without `config`, or searched with another one, in line with it.
+    fn prepare_root(&mut self, board: &G, config: &Config<G>, rng: &mut dyn RngCore) {
+        if config.symmetries {
+            self.merge_symmetric_moves(ROOT, board);
+        }
+        if config.widening.is_some() {
+            let root = &mut self.nodes[ROOT];
+            root.possible_moves =
+                unlock_order(board, std::mem::take(&mut root.possible_moves), rng);
+        }
+        if config.selection.uses_priors() {
+            let root = &mut self.nodes[ROOT];
+            let expanded_moves: Moves<G> = root
+                .children
+                .iter()
+                .map(|edge| edge.the_move.clone())
+                .collect();
+            let expanded_weights = board.move_weights(&expanded_moves);
+            root.total_weight =
+                total_weight(board, &root.possible_moves) + expanded_weights.iter().sum::<f64>();
+            for (edge, weight) in root.children.iter_mut().zip(expanded_weights) {
+                edge.prior = weight / root.total_weight;
+            }
+        }
+    }
+
+    /// Drops the moves left to expand at `id` that lead to the same position, up to a symmetry,
+    /// as one of its children or an earlier move.
+    fn merge_symmetric_moves(&mut self, id: NodeId, board: &G) {
+        let node = &mut self.nodes[id];
+        let taken = node
+            .children
+            .iter()
+            .map(|edge| edge.the_move.clone())
+            .collect();
+        node.possible_moves =
+            distinct_moves(board, std::mem::take(&mut node.possible_moves), taken);
+    }
+
+    /// Moves a root that holds a symmetric version of `board` onto `board` itself, `symmetry` mapping
+    /// `board` onto the stored version. Only the moves of the root change, its children keep
+    /// their own boards and get the symmetry from the new moves onto them.
+    fn reframe_root(&mut self, board: G, symmetry: usize) {
+        let legal_moves = board.all_legal_moves();
+        let real_move = |stored: &G::Move| {
+            legal_moves
+                .iter()
+                .find(|a_move| board.transform_move(a_move, symmetry) == *stored)
+                .unwrap()
+                .clone()
+        };
+        let old_hash = self.nodes[ROOT].hash;
+        let child_hashes: Vec<u64> = self.nodes[ROOT]
+            .children
+            .iter()
+            .map(|edge| self.nodes[edge.node].hash)
+            .collect();
+        let root = &mut self.nodes[ROOT];
+        root.possible_moves = root.possible_moves.iter().map(real_move).collect();
+        for (edge, hash) in root.children.iter_mut().zip(child_hashes) {
+            edge.the_move = real_move(&edge.the_move);
+            let mut child_board = board.clone();
+            child_board.apply_move(edge.the_move.clone());
+            edge.symmetry = (0..child_board.symmetry_count())
+                .find(|&symmetry| child_board.transformed(symmetry).zobrist_hash() == hash)
+                .unwrap_or(0);
+        }
+        root.hash = board.zobrist_hash();
+        root.origin = Origin::Root(board);
+        // the children of the root were all reached from it first, compact sees them first
+        let origins: Vec<(NodeId, G::Move, usize)> = self.nodes[ROOT]
+            .children
+            .iter()
+            .map(|edge| (edge.node, edge.the_move.clone(), edge.symmetry))
+            .collect();
+        for (child, the_move, symmetry) in origins {
+            self.nodes[child].origin = Origin::Parent(ROOT, the_move, symmetry);
+        }
+        // a table keyed by canonical forms already fits, one keyed by plain hashes needs the new one
+        if self.table.get(&old_hash) == Some(&ROOT) {
+            self.table.remove(&old_hash);
+            self.table.insert(self.nodes[ROOT].hash, ROOT);
+        }
+    }
+
+    /// Rebuilds the arena so it only holds `new_root` and everything below it, in breadth first order.
+    fn compact(self, new_root: NodeId, board: G) -> Tree<G> {
+        let mut old_nodes: Vec<Option<Node<G>>> = self.nodes.into_iter().map(Some).collect();
+        let mut new_ids: Vec<Option<NodeId>> = vec![None; old_nodes.len()];
+        let mut new_origins: Vec<Option<(NodeId, G::Move, usize)>> = Vec::new();
+        new_origins.resize_with(old_nodes.len(), || None);
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([new_root]);
+        new_ids[new_root] = Some(0);
+        while let Some(old_id) = queue.pop_front() {
+            let new_id = order.len();
+            order.push(old_id);
+            for edge in &old_nodes[old_id].as_ref().unwrap().children {
+                if new_ids[edge.node].is_none() {
+                    // breadth first, so ids are handed out in the order the nodes get visited
+                    new_ids[edge.node] = Some(order.len() + queue.len());
+                    new_origins[edge.node] = Some((new_id, edge.the_move.clone(), edge.symmetry));
+                    queue.push_back(edge.node);
+                }
+            }
+        }
+        let mut nodes = Vec::with_capacity(order.len());
+        let mut table = HashMap::new();
+        for old_id in order {
+            let mut node = old_nodes[old_id].take().unwrap();
+            for edge in node.children.iter_mut() {
+                edge.node = new_ids[edge.node].unwrap();
+            }
+            node.origin = match new_origins[old_id].take() {
+                Some((parent, the_move, symmetry)) => Origin::Parent(parent, the_move, symmetry),
+                None => {
+                    node.board = None;
+                    Origin::Root(board.clone())
+                }
+            };
+            nodes.push(node);
+        }
+        for (key, old_id) in self.table {
+            if let Some(new_id) = new_ids[old_id] {
+                table.insert(key, new_id);
+            }
+        }
+        Tree { nodes, table }
+    }
+}
+
+pub fn create_root_node<G: Game>(board: G) -> Tree<G> {
+    let root = Node::new(
+        &board,
+        Origin::Root(board.clone()),
+        0,
+        &Config {
+            board_cache: BoardCache::Incremental,
+            symmetries: false,
+            ..Config::default()
+        },
+    );
+    let table = HashMap::from([(root.hash, ROOT)]);
+    Tree {
+        nodes: vec![root],
+        table,
+    }
+}
+
+/// Makes `node` the new root, dropping everything that is not below it.
+pub fn trim_tree<G: Game>(tree: Tree<G>, node: NodeId) -> Tree<G> {
+    let board = extract_board(&tree, node);
+    tree.compact(node, board)
+}
+
+/// Makes the position after `the_move` the new root, keeping the statistics of the subtree below
+/// it and dropping everything else. The child is found by its position, so a move the root only
+/// holds as a symmetric version still reuses its subtree, and the new root is mapped onto the
+/// actual board. A move that was never expanded starts a fresh tree.
+pub fn advance<G: Game>(tree: Tree<G>, the_move: &G::Move) -> Result<Tree<G>, String> {
+    let mut board = tree.root_board().clone();
+    if !board.all_legal_moves().contains(the_move) {
+        return Err(format!("{the_move:?} is not a legal move"));
+    }
+    board.apply_move(the_move.clone());
+    let found = tree.root().children.iter().find_map(|edge| {
+        let hash = tree.nodes[edge.node].hash;
+        (0..board.symmetry_count())
+            .find(|&symmetry| board.transformed(symmetry).zobrist_hash() == hash)
+            .map(|symmetry| (edge.node, symmetry))
+    });
+    let Some((child, symmetry)) = found else {
+        return Ok(create_root_node(board));
+    };
+    let mut tree = tree.compact(child, board.transformed(symmetry));
+    if symmetry != 0 {
+        tree.reframe_root(board, symmetry);
+    }
+    Ok(tree)
+}
+
+/// Searches `tree` as long as `mode` allows and returns it together with the move the engine
+/// plays, picked by [`best_child`].
+pub fn engine<G: Game>(
+    mut tree: Tree<G>,
+    mode: Mode,
+    config: &Config<G>,
+) -> (Tree<G>, Option<G::Move>) {
+    let board = extract_board(&tree, ROOT);
+    println!("{board}");
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let rng: &mut dyn RngCore = &mut rng;
+    tree.prepare_root(&board, config, rng);
+    let budget = Budget::new(&mode, &tree, &board, &config.stop);
+    let reporter = Reporter::new(config.progress.as_ref());
+    let tree = match config.parallelism {
+        Parallelism::Leaf => {
+            thread::scope(|scope| {
+                let pool = WorkerPool::new(scope, config.workers, config);
+                let mut iterations = 0;
+                let mut can_prune = true;
+                while !budget.exhausted(iterations, &tree) && !tree.solved() {
+                    if can_prune && tree.is_full(config) {
+                        can_prune = tree.make_room(config);
+                    }
+                    algorithm(&mut tree, &pool, config, rng);
+                    iterations += 1;
+                    reporter.tick(iterations, &tree);
+                }
+            });
+            tree
+        }
+        Parallelism::Tree => parallel::tree_parallel(tree, &budget, config, rng, &reporter),
+        Parallelism::Root => parallel::root_parallel(tree, &budget, config, rng, &reporter),
+    };
+    config.stop.reset();
+    let best_move = best_child(&tree, config, rng).map(|edge| edge.the_move.clone());
+    (tree, best_move)
+}
+
+/// Stops a running [`engine`] call from another thread, clones share the same signal.
+/// A stop that arrives before the search starts ends it right away,
+/// the engine resets the signal when it returns.
+#[derive(Clone, Default, Debug)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Withdraws a stop that no search has seen yet.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// When a search has to stop, worked out from its [`Mode`].
+#[derive(Clone)]
+struct Budget {
+    iterations: Option<usize>,
+    stop_time: Option<Instant>,
+    nodes: Option<usize>,
+    stop: StopHandle,
+    /// The soft limit of a [`Mode::Clock`] search, `stop_time` being the hard one.
+    soft_stop_time: Option<Instant>,
+    start: Instant,
+    start_simulations: u32,
+}
+
+impl Budget {
+    fn new<G: Game>(mode: &Mode, tree: &Tree<G>, board: &G, stop: &StopHandle) -> Self {
+        let start = Instant::now();
+        let mut soft_time = None;
+        let (iterations, time, nodes) = match *mode {
+            Mode::Iterations(iterations) => (Some(iterations), None, None),
+            Mode::Time(duration) => (None, Some(duration), None),
+            Mode::Infinite => (None, None, None),
+            Mode::Limits {
+                iterations,
+                time,
+                nodes,
+            } => (iterations, time, nodes),
+            Mode::Clock(clock) => {
+                let (soft, hard) = clock.limits(board.moves_left());
+                soft_time = Some(soft);
+                (None, Some(hard), None)
+            }
+        };
+        Budget {
+            iterations,
+            stop_time: time.map(|duration| start.add(duration)),
+            nodes,
+            stop: stop.clone(),
+            soft_stop_time: soft_time.map(|duration| start.add(duration)),
+            start,
+            start_simulations: tree.root().simulations(),
+        }
+    }
+
+    /// The share of one of `parts` searches running side by side, counting from the same tree.
+    fn split(&self, parts: usize) -> Budget {
+        Budget {
+            iterations: self.iterations.map(|iterations| iterations.div_ceil(parts)),
+            stop_time: self.stop_time,
+            nodes: self.nodes.map(|nodes| nodes.div_ceil(parts)),
+            stop: self.stop.clone(),
+            soft_stop_time: self.soft_stop_time,
+            start: self.start,
+            start_simulations: self.start_simulations,
+        }
+    }
+
+    /// Whether a search that did `iterations` iterations on `tree` has to stop.
+    fn exhausted<G: Game>(&self, iterations: usize, tree: &Tree<G>) -> bool {
+        self.iterations.is_some_and(|limit| iterations >= limit)
+            || self.nodes.is_some_and(|limit| tree.nodes.len() >= limit)
+            || self
+                .stop_time
+                .is_some_and(|stop_time| Instant::now() >= stop_time)
+            || self.stop.is_stopped()
+            || self.settled(tree)
+    }
+
+    /// Whether a clock search can stop before its hard limit: once the most visited root child can
+    /// not be overtaken any more at the current speed, or after the soft limit once it is also
+    /// the child with the best value.
+    fn settled<G: Game>(&self, tree: &Tree<G>) -> bool {
+        let (Some(soft_stop_time), Some(stop_time)) = (self.soft_stop_time, self.stop_time) else {
+            return false;
+        };
+        let root = tree.root();
+        let visited = root.children.iter().filter(|edge| edge.visits() > 0);
+        let Some(best) = visited.clone().max_by_key(|edge| edge.visits()) else {
+            return false;
+        };
+        let runner_up = visited
+            .clone()
+            .filter(|edge| edge.node != best.node)
+            .map(Edge::visits)
+            .max()
+            .unwrap_or(0);
+        let now = Instant::now();
+        let searched = root.simulations().saturating_sub(self.start_simulations) as f64;
+        if searched == 0.0 {
+            // the speed is unknown until the first rollouts are back
+            return false;
+        }
+        let speed = searched / now.duration_since(self.start).as_secs_f64().max(1e-3);
+        let still_possible = speed * stop_time.saturating_duration_since(now).as_secs_f64();
+        if root.possible_moves.is_empty() && (best.visits() - runner_up) as f64 > still_possible {
+            return true;
+        }
+        now >= soft_stop_time
+            && visited
+                .map(|edge| tree.nodes[edge.node].mean_reward())
+                .all(|reward| reward <= tree.nodes[best.node].mean_reward())
+    }
+}
+
+fn algorithm<G: Game>(
+    tree: &mut Tree<G>,
+    pool: &WorkerPool<G>,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+) {
+    let (node_type, path, board) = selection_phase(tree, config);
+    match node_type {
+        NodeType::Leaf => {
+            let nodes = expansion_phase(tree, &path, board, config, pool.batch_size(), rng);
+            let (board_list, path_list): (Vec<G>, Vec<Path>) = nodes.into_iter().unzip();
+            for (rollout, path) in pool.simulate(board_list, rng).into_iter().zip(path_list) {
+                backpropagation(tree, &rollout, &path);
+            }
+        }
+        NodeType::End => {
+            //println!("Hit an end node");
+            let rollout = end_rollout(tree, &path, board, config, rng);
+            backpropagation(tree, &rollout, &path);
+        }
+    };
+}
+
+enum NodeType {
+    Leaf,
+    End,
+}
+
+/// The nodes one iteration went through from the root down, and the edge it took out of each.
+/// Backpropagation has to follow it since a node can have several parents.
+#[derive(Clone)]
+struct Path {
+    nodes: Vec<NodeId>,
+    edges: Vec<usize>,
+}
+
+impl Path {
+    fn leaf(&self) -> NodeId {
+        *self.nodes.last().unwrap()
+    }
+
+    fn extended(&self, edge: usize, node: NodeId) -> Path {
+        let mut path = self.clone();
+        path.edges.push(edge);
+        path.nodes.push(node);
+        path
+    }
+}
+
+/// Walks down to the node to expand next and returns the path to it together with its board.
+fn selection_phase<G: Game>(tree: &Tree<G>, config: &Config<G>) -> (NodeType, Path, G) {
+    let mut path = Path {
+        nodes: vec![ROOT],
+        edges: Vec::new(),
+    };
+    let mut board = match config.board_cache {
+        BoardCache::Incremental => Some(extract_board(tree, ROOT)),
+        _ => None,
+    };
+    loop {
+        let current = path.leaf();
+        let node = &tree.nodes[current];
+        if node.proven().is_some() {
+            let board = board.unwrap_or_else(|| extract_board(tree, current));
+            return (NodeType::End, path, board);
+        }
+        let expand = node.wants_expansion(config) && !tree.is_full(config);
+        if expand || node.children.is_empty() {
+            let board = board.unwrap_or_else(|| extract_board(tree, current));
+            return if expand {
+                (NodeType::Leaf, path, board)
+            } else {
+                (NodeType::End, path, board)
+            };
+        }
+        let mut edge_to_explore = 0;
+        let mut max_score = f64::NEG_INFINITY;
+        let node_visits = (node.simulations() + node.virtual_loss()) as f64;
+        for (index, edge) in node.children.iter().enumerate() {
+            let child_node = &tree.nodes[edge.node];
+            // a proven win ends the choice, a proven loss is never worth another look
+            match child_node.proven() {
+                Some(Output::Win) => {
+                    edge_to_explore = index;
+                    break;
+                }
+                Some(Output::Loss) => continue,
+                _ => {}
+            }
+            // the value is shared between all parents, the exploration only counts this edge
+            let virtual_loss = child_node.virtual_loss() as f64;
+            let mut candidate = Candidate {
+                value_sum: child_node.reward_sum(),
+                squares_sum: load_f64(&child_node.reward_squares),
+                visits: child_node.simulations() as f64 + virtual_loss,
+                edge_visits: edge.visits() as f64 + virtual_loss,
+                prior: edge.prior,
+            };
+            if let Some(rave) = &config.rave {
+                let amaf_visits = edge.amaf_visits.load(Ordering::Relaxed) as f64;
+                if amaf_visits > 0.0 && candidate.visits > 0.0 {
+                    let amaf_value = load_f64(&edge.amaf_value) / amaf_visits;
+                    let mean = candidate.value_sum / candidate.visits;
+                    candidate.value_sum =
+                        rave.blend(mean, amaf_value, candidate.edge_visits) * candidate.visits;
+                }
+            }
+            let score = config.selection.score(node_visits, &candidate);
+            if score > max_score {
+                max_score = score;
+                edge_to_explore = index;
+            }
+        }
+        let edge = &node.children[edge_to_explore];
+        if let Some(board) = &mut board {
+            board.apply_move(edge.the_move.clone());
+            if edge.symmetry != 0 {
+                *board = board.transformed(edge.symmetry);
+            }
+        }
+        path.edges.push(edge_to_explore);
+        path.nodes.push(edge.node);
+    }
+}
+
+/// Rebuilds the board of `node` by replaying the moves from the closest node that keeps its board.
+fn extract_board<G: Game>(tree: &Tree<G>, node: NodeId) -> G {
+    let mut moves = Vec::new();
+    let mut current = node;
+    let mut board = loop {
+        let node_content = &tree.nodes[current];
+        if let Some(board) = &node_content.board {
+            break board.clone();
+        }
+        match &node_content.origin {
+            Origin::Root(board_ref) => break board_ref.clone(),
+            Origin::Parent(parent, node_move, symmetry) => {
+                moves.push((node_move, *symmetry));
+                current = *parent;
+            }
+        }
+    };
+    for (node_move, symmetry) in moves.into_iter().rev() {
+        board.apply_move(node_move.clone());
+        if symmetry != 0 {
+            board = board.transformed(symmetry);
+        }
+    }
+    board
+}
+
+fn total_weight<G: Game>(board: &G, moves: &[G::Move]) -> f64 {
+    board.move_weights(moves).iter().sum()
+}
+
+/// The moves that do not lead to the same position, up to a symmetry of `board`, as a move in
+/// `kept` or an earlier one.
+fn distinct_moves<G: Game>(board: &G, moves: Moves<G>, mut kept: Moves<G>) -> Moves<G> {
+    let hash = board.zobrist_hash();
+    let stabilizer: Vec<usize> = (1..board.symmetry_count())
+        .filter(|&symmetry| board.transformed(symmetry).zobrist_hash() == hash)
+        .collect();
+    if stabilizer.is_empty() {
+        return moves;
+    }
+    let already_kept = kept.len();
+    for a_move in moves {
+        let symmetric_to_kept = stabilizer
+            .iter()
+            .any(|&symmetry| kept.contains(&board.transform_move(&a_move, symmetry)));
+        if !symmetric_to_kept {
+            kept.push(a_move);
+        }
+    }
+    kept.split_off(already_kept)
+}
+
+/// Sorts `moves` so the one with the highest weight comes last, to be unlocked first.
+/// Moves of the same weight end up in random order.
+fn unlock_order<G: Game>(board: &G, mut moves: Moves<G>, rng: &mut dyn RngCore) -> Moves<G> {
+    moves.shuffle(rng);
+    let weights = board.move_weights(&moves);
+    let mut weighted: Vec<(f64, G::Move)> = weights.into_iter().zip(moves).collect();
+    weighted.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    weighted.into_iter().map(|(_, a_move)| a_move).collect()
+}
+
+/// Adds up to `count` children to the leaf at the end of `path` and returns their boards and paths.
+fn expansion_phase<G: Game>(
+    tree: &mut Tree<G>,
+    path: &Path,
+    board: G,
+    config: &Config<G>,
+    count: usize,
+    rng: &mut dyn RngCore,
+) -> Vec<(G, Path)> {
+    let leaf_node = path.leaf();
+    let mut node_list = Vec::with_capacity(count);
+    for _ in 0..count {
+        if !tree.nodes[leaf_node].wants_expansion(config) {
+            break;
+        }
+        let possible_next_moves = &mut tree.nodes[leaf_node].possible_moves;
+        let next_move = if config.widening.is_some() {
+            possible_next_moves.pop().unwrap()
+        } else {
+            possible_next_moves.remove(rng.gen_range(0..possible_next_moves.len()))
+        };
+        let (edge, new_node_board) = tree.add_child(leaf_node, &board, next_move, config, rng);
+        let new_node = tree.nodes[leaf_node].children[edge].node;
+        node_list.push((new_node_board, path.extended(edge, new_node)));
+    }
+    node_list
+}
+
+/// The result of a node without moves to expand, the proven one if the solver knows it.
+fn end_rollout<G: Game>(
+    tree: &Tree<G>,
+    path: &Path,
+    board: G,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+) -> Rollout<G> {
+    match tree.nodes[path.leaf()].proven() {
+        Some(outcome) => Rollout {
+            result: Evaluation::finished(outcome, outcome.reward(config.draw_value)),
+            moves: config.rave.is_some().then(Vec::new),
+        },
+        None => simulation_phase(board, config, rng),
+    }
+}
+
+fn backpropagation<G: Game>(tree: &Tree<G>, rollout: &Rollout<G>, path: &Path) {
+    if tree.nodes[path.leaf()].proven().is_some() {
+        propagate_proof(tree, path);
+    }
+    if let Some(moves) = &rollout.moves {
+        update_amaf(tree, rollout.result, path, moves);
+    }
+    let mut value = rollout.result;
+    for (depth, &id) in path.nodes.iter().enumerate().rev() {
+        let node_content = &tree.nodes[id];
+        node_content.simulations.fetch_add(1, Ordering::Relaxed);
+        match value.outcome {
+            Some(Output::Win) => node_content.win_count.fetch_add(1, Ordering::Relaxed),
+            Some(Output::Draw) => node_content.draw_count.fetch_add(1, Ordering::Relaxed),
+            Some(Output::Loss) => 0,
+            None => node_content.estimate_count.fetch_add(1, Ordering::Relaxed),
+        };
+        add_f64(&node_content.reward_sum, value.reward);
+        add_f64(&node_content.reward_squares, value.reward * value.reward);
+        value = value.reversed();
+        if depth > 0 {
+            let parent = &tree.nodes[path.nodes[depth - 1]];
+            parent.children[path.edges[depth - 1]]
+                .visits
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Counts the result for every child of a node on `path` whose move the player to move there
+/// played later on, in the tree or in the rollout.
+///
+/// The moves below an edge with a symmetry are moves on the transformed board of its child, so
+/// going up the path they are mapped back through every such edge before they are compared.
+fn update_amaf<G: Game>(tree: &Tree<G>, value: Evaluation, path: &Path, rollout_moves: &[G::Move]) {
+    // the games map moves the same way on all of their positions, the root board stands in for them
+    let board = tree.root_board();
+    let mut moves: VecDeque<G::Move> = rollout_moves.iter().cloned().collect();
+    let leaf = path.nodes.len() - 1;
+    for (depth, &id) in path.nodes.iter().enumerate().rev() {
+        if depth < leaf {
+            let edge = &tree.nodes[id].children[path.edges[depth]];
+            if edge.symmetry != 0 {
+                let inverse = board.inverse_symmetry(edge.symmetry);
+                for a_move in moves.iter_mut() {
+                    *a_move = board.transform_move(a_move, inverse);
+                }
+            }
+            moves.push_front(edge.the_move.clone());
+        }
+        // `value` is meant for the player that moved into the leaf
+        let value = if (leaf - depth) % 2 == 1 {
+            value
+        } else {
+            value.reversed()
+        }
+        .reward;
+        #[cfg(test)]
+        tests::assert_playable(tree, id, &moves);
+        let own_moves: Vec<&G::Move> = moves.iter().step_by(2).collect();
+        for edge in &tree.nodes[id].children {
+            if own_moves.contains(&&edge.the_move) {
+                edge.add_amaf(value);
+            }
+        }
+    }
+}
+
+/// Proves the nodes on `path` above its proven leaf for as long as their children allow it.
+fn propagate_proof<G: Game>(tree: &Tree<G>, path: &Path) {
+    for &id in path.nodes.iter().rev().skip(1) {
+        let node = &tree.nodes[id];
+        match proof_from_children(tree, node) {
+            Some(value) => node.prove(value),
+            None => break,
+        }
+    }
+}
+
+/// A child that wins for the player to move proves a loss for the player that moved into `node`.
+/// Once every move is expanded and proven, the best of them decides the value.
+fn proof_from_children<G: Game>(tree: &Tree<G>, node: &Node<G>) -> Option<Output> {
+    let mut best = Output::Loss;
+    let mut all_proven = node.possible_moves.is_empty() && !node.children.is_empty();
+    for edge in &node.children {
+        match tree.nodes[edge.node].proven() {
+            Some(Output::Win) => return Some(Output::Loss),
+            Some(Output::Draw) => best = Output::Draw,
+            Some(Output::Loss) => {}
+            None => all_proven = false,
+        }
+    }
+    all_proven.then(|| best.reversed())
+}
+
+fn encode_proof(proof: Option<Output>) -> u8 {
+    match proof {
+        None => 0,
+        Some(Output::Win) => 1,
+        Some(Output::Draw) => 2,
+        Some(Output::Loss) => 3,
+    }
+}
+
+fn decode_proof(code: u8) -> Option<Output> {
+    match code {
+        1 => Some(Output::Win),
+        2 => Some(Output::Draw),
+        3 => Some(Output::Loss),
+        _ => None,
+    }
+}
+
+/// Adds `value` to the `f64` stored as bits in `atomic`.
+fn add_f64(atomic: &AtomicU64, value: f64) {
+    let _ = atomic.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+        Some((f64::from_bits(bits) + value).to_bits())
+    });
+}
+
+fn load_f64(atomic: &AtomicU64) -> f64 {
+    f64::from_bits(atomic.load(Ordering::Relaxed))
+}
+
+/// How a rollout ended, with the moves played in it when RAVE needs them.
+struct Rollout<G: Game> {
+    result: Evaluation,
+    moves: Option<Moves<G>>,
+}
+
+/// The result of a rollout for one of the players.
+#[derive(Clone, Copy, Debug)]
+pub struct Evaluation {
+    /// How the game ended, `None` if the rollout was cut off and [`Game::evaluate`] guessed.
+    pub outcome: Option<Output>,
+    /// The reward between a loss at zero and a win at one.
+    pub reward: f64,
+}
+
+impl Evaluation {
+    fn finished(outcome: Output, reward: f64) -> Evaluation {
+        Evaluation {
+            outcome: Some(outcome),
+            reward,
+        }
+    }
+
+    fn estimate(reward: f64) -> Evaluation {
+        Evaluation {
+            outcome: None,
+            reward,
+        }
+    }
+
+    /// The same result seen by the other player.
+    pub fn reversed(self) -> Evaluation {
+        Evaluation {
+            outcome: self.outcome.map(Output::reversed),
+            reward: 1.0 - self.reward,
+        }
+    }
+}
+
+fn simulation_phase<G: Game>(
+    mut board: G,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+) -> Rollout<G> {
+    let player = match board.next_to_move() {
+        Player::Host => Player::Guest, //DO NOT TOUCH! IMPORTANT
+        Player::Guest => Player::Host, //DO NOT TOUCH! IMPORTANT
+    };
+    #[cfg(debug_assertions)]
+    println!("{board}");
+    let mut moves = config.rave.is_some().then(Vec::new);
+    let mut plies = 0;
+    let result = loop {
+        let board_state = board.finished(player);
+        if let Some(outcome) = board_state {
+            break Evaluation::finished(outcome, board.reward(player, outcome, config.draw_value));
+        } else if config.rollout_depth.is_some_and(|depth| plies >= depth) {
+            break Evaluation::estimate(board.evaluate(player));
+        } else {
+            let Some(next_move) = config.rollout.choose_move(&board, rng) else {
+                //petty draws
+                break Evaluation::finished(Output::Draw, config.draw_value);
+            };
+            #[cfg(debug_assertions)]
+            println!("{next_move:?}");
+            if let Some(moves) = &mut moves {
+                moves.push(next_move.clone());
+            }
+            board.apply_move(next_move);
+            plies += 1;
+            #[cfg(debug_assertions)]
+            println!("{board}");
+        }
+    };
+    Rollout { result, moves }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Player {
+    Host,
+    Guest,
+}
+
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::Host => Player::Guest,
+            Player::Guest => Player::Host,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Output {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Output {
+    /// The same result seen by the other player.
+    pub fn reversed(self) -> Output {
+        match self {
+            Output::Win => Output::Loss,
+            Output::Draw => Output::Draw,
+            Output::Loss => Output::Win,
+        }
+    }
+
+    /// The plain reward of the result, with a draw worth `draw_value`.
+    pub fn reward(self, draw_value: f64) -> f64 {
+        match self {
+            Output::Win => 1.0,
+            Output::Draw => draw_value,
+            Output::Loss => 0.0,
+        }
+    }
+}
+
+pub enum Mode {
+    Iterations(usize),
+    Time(Duration),
+    /// Search until the [`StopHandle`] of the config is stopped or the position is solved.
+    Infinite,
+    /// Search on a game clock, see [`Clock`].
+    Clock(Clock),
+    /// Search until the first of the given limits is reached, `nodes` counting the whole tree.
+    Limits {
+        iterations: Option<usize>,
+        time: Option<Duration>,
+        nodes: Option<usize>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ultimate_tic_tac_toe::Board;
+
+    fn seeded_search(parallelism: Parallelism) -> Vec<u8> {
+        let config = Config {
+            parallelism,
+            workers: 2,
+            seed: Some(7),
+            ..Config::default()
+        };
+        let tree = create_root_node(Board::empty());
+        let (tree, _) = engine(tree, Mode::Iterations(2_000), &config);
+        let mut saved = Vec::new();
+        save_tree(&tree, &mut saved).unwrap();
+        saved
+    }
+
+    /// Replays the moves [`update_amaf`] credits at `id` from the board of the node.
+    pub(super) fn assert_playable<G: Game>(tree: &Tree<G>, id: NodeId, moves: &VecDeque<G::Move>) {
+        let mut board = extract_board(tree, id);
+        for (ply, a_move) in moves.iter().enumerate() {
+            assert!(
+                board.all_legal_moves().contains(a_move),
+                "AMAF move {a_move:?} illegal at ply {ply} of {}",
+                moves.len()
+            );
+            board.apply_move(a_move.clone());
+        }
+    }
+
+    #[test]
+    fn amaf_moves_are_legal_below_symmetric_edges() {
+        for seed in 0..20 {
+            let config = Config {
+                rave: Some(Rave::default()),
+                seed: Some(seed),
+                ..Config::default()
+            };
+            let tree = create_root_node(crate::tic_tac_toe::Board::empty());
+            engine(tree, Mode::Iterations(500), &config);
+        }
+    }
+
+    #[test]
+    fn root_parallel_merges_symmetric_moves_into_one_child() {
+        use crate::tic_tac_toe::{Board, Move};
+        for seed in 0..10 {
+            let config = Config {
+                parallelism: Parallelism::Root,
+                workers: 4,
+                seed: Some(seed),
+                ..Config::default()
+            };
+            let tree = create_root_node(Board::empty());
+            let (tree, _) = engine(tree, Mode::Iterations(300), &config);
+            let tree = advance(tree, &Move::Place(2, 2)).unwrap();
+            let (tree, _) = engine(tree, Mode::Iterations(300), &config);
+            let mut children: Vec<NodeId> =
+                tree.root().children.iter().map(|edge| edge.node).collect();
+            children.sort_unstable();
+            children.dedup();
+            assert_eq!(children.len(), tree.root().children.len());
+        }
+    }
+
+    #[test]
+    fn same_seed_saves_the_same_tree() {
+        for parallelism in [Parallelism::Leaf, Parallelism::Root] {
+            assert!(seeded_search(parallelism) == seeded_search(parallelism));
+        }
+    }
+}
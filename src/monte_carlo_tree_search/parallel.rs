@@ -0,0 +1,235 @@
+use super::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::RwLock;
+
+/// Lets `config.workers` threads descend the same tree at once.
+///
+/// Selection and backpropagation only need shared access thanks to the atomic counters,
+/// expanding a node takes the lock exclusively. Every thread walking through a node leaves a
+/// virtual loss on it until its rollout is back, so the others are steered towards different leaves.
+pub(super) fn tree_parallel<G: Game>(
+    tree: Tree<G>,
+    budget: &Budget,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+    reporter: &Reporter<G>,
+) -> Tree<G> {
+    let shared_tree = RwLock::new(tree);
+    let iterations = &AtomicUsize::new(0);
+    // pruning renumbers the nodes, so it waits until no iteration holds on to a path
+    let iterating = &RwLock::new(());
+    let can_prune = &AtomicBool::new(true);
+    thread::scope(|scope| {
+        let shared_tree = &shared_tree;
+        for _ in 0..config.workers.max(1) {
+            // which thread gets to which node first still depends on timing
+            let mut rng = StdRng::seed_from_u64(rng.next_u64());
+            scope.spawn(move || loop {
+                let iteration = iterations.fetch_add(1, Ordering::Relaxed);
+                let full = {
+                    let tree = shared_tree.read().unwrap();
+                    if budget.exhausted(iteration, &tree) || tree.solved() {
+                        break;
+                    }
+                    reporter.tick(iteration, &tree);
+                    tree.is_full(config)
+                };
+                if full && can_prune.load(Ordering::Relaxed) {
+                    let _alone = iterating.write().unwrap();
+                    let mut tree = shared_tree.write().unwrap();
+                    if tree.is_full(config) && !tree.make_room(config) {
+                        can_prune.store(false, Ordering::Relaxed);
+                    }
+                }
+                let _iterating = iterating.read().unwrap();
+                shared_algorithm(shared_tree, config, &mut rng);
+            });
+        }
+    });
+    shared_tree.into_inner().unwrap()
+}
+
+/// Searches `config.workers` independent trees from the same root, one per thread, and adds
+/// the root children statistics of the extra trees to the matching children of `tree`.
+///
+/// Iteration and node budgets are split between the trees, a time budget applies to each of them.
+pub(super) fn root_parallel<G: Game>(
+    mut tree: Tree<G>,
+    budget: &Budget,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+    reporter: &Reporter<G>,
+) -> Tree<G> {
+    let tree_count = config.workers.max(1);
+    let budget = budget.split(tree_count);
+    // only `tree` may bring simulations from an earlier search, the extra trees start empty
+    let fresh_budget = Budget {
+        start_simulations: 0,
+        ..budget.clone()
+    };
+    let board = extract_board(&tree, ROOT);
+    let extra_trees: Vec<Tree<G>> = thread::scope(|scope| {
+        let handles: Vec<_> = (1..tree_count)
+            .map(|_| {
+                let mut extra_tree = create_root_node(board.clone());
+                let mut extra_rng = StdRng::seed_from_u64(rng.next_u64());
+                extra_tree.prepare_root(&board, config, &mut extra_rng);
+                let budget = &fresh_budget;
+                scope.spawn(move || {
+                    let silent = Reporter::new(None);
+                    search_alone(&mut extra_tree, budget, config, &mut extra_rng, &silent);
+                    extra_tree
+                })
+            })
+            .collect();
+        search_alone(&mut tree, &budget, config, rng, reporter);
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    for extra_tree in extra_trees {
+        merge_root_children(&mut tree, &board, extra_tree, config, rng);
+    }
+    tree
+}
+
+fn search_alone<G: Game>(
+    tree: &mut Tree<G>,
+    budget: &Budget,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+    reporter: &Reporter<G>,
+) {
+    thread::scope(|scope| {
+        let pool = WorkerPool::new(scope, 0, config);
+        let mut iterations = 0;
+        let mut can_prune = true;
+        while !budget.exhausted(iterations, tree) && !tree.solved() {
+            if can_prune && tree.is_full(config) {
+                can_prune = tree.make_room(config);
+            }
+            algorithm(tree, &pool, config, rng);
+            iterations += 1;
+            reporter.tick(iterations, tree);
+        }
+    });
+}
+
+/// Adds the statistics of the root and its children in `other` to `tree`, matching children by
+/// their position. Children only `other` has expanded are created in `tree`.
+fn merge_root_children<G: Game>(
+    tree: &mut Tree<G>,
+    board: &G,
+    other: Tree<G>,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+) {
+    tree.nodes[ROOT].add_statistics(&other.nodes[ROOT]);
+    for other_edge in &other.nodes[ROOT].children {
+        // a reused tree may hold another move of a symmetric pair than the fresh ones
+        let mut child_board = board.clone();
+        child_board.apply_move(other_edge.the_move.clone());
+        let symmetries = if config.symmetries {
+            child_board.symmetry_count()
+        } else {
+            1
+        };
+        let child_hashes: Vec<u64> = (0..symmetries)
+            .map(|symmetry| child_board.transformed(symmetry).zobrist_hash())
+            .collect();
+        let matching_edge = tree.nodes[ROOT]
+            .children
+            .iter()
+            .position(|edge| child_hashes.contains(&tree.nodes[edge.node].hash));
+        let edge = match matching_edge {
+            Some(edge) => edge,
+            None => {
+                let root = &mut tree.nodes[ROOT];
+                root.possible_moves
+                    .retain(|possible_move| *possible_move != other_edge.the_move);
+                let edge = tree
+                    .add_child(ROOT, board, other_edge.the_move.clone(), config, rng)
+                    .0;
+                if config.symmetries {
+                    tree.merge_symmetric_moves(ROOT, board);
+                }
+                edge
+            }
+        };
+        let edge = &tree.nodes[ROOT].children[edge];
+        edge.visits
+            .fetch_add(other_edge.visits(), Ordering::Relaxed);
+        edge.add_amaf_statistics(other_edge);
+        tree.nodes[edge.node].add_statistics(&other.nodes[other_edge.node]);
+        if let Some(value) = other.nodes[other_edge.node].proven() {
+            tree.nodes[edge.node].prove(value);
+        }
+    }
+    if let Some(value) = proof_from_children(tree, &tree.nodes[ROOT]) {
+        tree.nodes[ROOT].prove(value);
+    }
+}
+
+fn shared_algorithm<G: Game>(
+    shared_tree: &RwLock<Tree<G>>,
+    config: &Config<G>,
+    rng: &mut dyn RngCore,
+) {
+    let (node_type, path, board) = {
+        let tree = shared_tree.read().unwrap();
+        let (node_type, path, board) = selection_phase(&tree, config);
+        add_virtual_loss(&tree, &path, 1);
+        (node_type, path, board)
+    };
+    match node_type {
+        NodeType::Leaf => {
+            let nodes = {
+                let mut tree = shared_tree.write().unwrap();
+                // every thread plays its own rollouts one after the other
+                let nodes = expansion_phase(&mut tree, &path, board, config, 1, rng);
+                // one virtual loss per rollout, the selection already left the first one
+                if nodes.is_empty() {
+                    remove_virtual_loss(&tree, &path);
+                } else {
+                    add_virtual_loss(&tree, &path, nodes.len() as u32 - 1);
+                }
+                for (_board, child_path) in &nodes {
+                    tree.nodes[child_path.leaf()]
+                        .virtual_loss
+                        .fetch_add(1, Ordering::Relaxed);
+                }
+                nodes
+            };
+            let rollouts: Vec<(Rollout<G>, Path)> = nodes
+                .into_iter()
+                .map(|(board, child_path)| (simulation_phase(board, config, rng), child_path))
+                .collect();
+            let tree = shared_tree.read().unwrap();
+            for (rollout, child_path) in rollouts {
+                backpropagation(&tree, &rollout, &child_path);
+                remove_virtual_loss(&tree, &child_path);
+            }
+        }
+        NodeType::End => {
+            let tree = shared_tree.read().unwrap();
+            let rollout = end_rollout(&tree, &path, board, config, rng);
+            backpropagation(&tree, &rollout, &path);
+            remove_virtual_loss(&tree, &path);
+        }
+    }
+}
+
+fn add_virtual_loss<G: Game>(tree: &Tree<G>, path: &Path, amount: u32) {
+    for &id in &path.nodes {
+        tree.nodes[id]
+            .virtual_loss
+            .fetch_add(amount, Ordering::Relaxed);
+    }
+}
+
+fn remove_virtual_loss<G: Game>(tree: &Tree<G>, path: &Path) {
+    for &id in &path.nodes {
+        tree.nodes[id].virtual_loss.fetch_sub(1, Ordering::Relaxed);
+    }
+}
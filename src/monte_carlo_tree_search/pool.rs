@@ -0,0 +1,81 @@
+use super::{simulation_phase, Config, Game, Rollout};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::Scope;
+
+/// Worker threads that live for one [`engine`](super::engine) call and play out the rollouts.
+pub(super) struct WorkerPool<G: Game> {
+    jobs: Option<mpsc::Sender<(usize, G, u64)>>,
+    results: mpsc::Receiver<(usize, Rollout<G>)>,
+    config: Config<G>,
+    workers: usize,
+}
+
+impl<G: Game> WorkerPool<G> {
+    /// Spawns `workers` threads on `scope`. With zero workers the rollouts run on the calling thread.
+    /// The rollouts are played as `config` asks for.
+    pub(super) fn new<'scope>(
+        scope: &'scope Scope<'scope, '_>,
+        workers: usize,
+        config: &Config<G>,
+    ) -> Self {
+        let (job_sender, job_receiver) = mpsc::channel::<(usize, G, u64)>();
+        let (result_sender, results) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        for _ in 0..workers {
+            let job_receiver = job_receiver.clone();
+            let result_sender = result_sender.clone();
+            let config = config.clone();
+            scope.spawn(move || loop {
+                let job = job_receiver.lock().unwrap().recv();
+                let Ok((index, board, seed)) = job else {
+                    break;
+                };
+                let rng = &mut StdRng::seed_from_u64(seed);
+                if result_sender
+                    .send((index, simulation_phase(board, &config, rng)))
+                    .is_err()
+                {
+                    break;
+                }
+            });
+        }
+        WorkerPool {
+            jobs: (workers > 0).then_some(job_sender),
+            results,
+            config: config.clone(),
+            workers,
+        }
+    }
+
+    /// How many rollouts a batch should hold to keep all workers busy.
+    pub(super) fn batch_size(&self) -> usize {
+        self.workers.max(1)
+    }
+
+    /// Plays out all boards of the batch and returns their rollouts in the same order.
+    /// Every rollout draws from a generator of its own seeded by `rng`, whichever thread plays it.
+    pub(super) fn simulate(&self, boards: Vec<G>, rng: &mut dyn RngCore) -> Vec<Rollout<G>> {
+        let seeds: Vec<u64> = boards.iter().map(|_| rng.next_u64()).collect();
+        let Some(jobs) = &self.jobs else {
+            return boards
+                .into_iter()
+                .zip(seeds)
+                .map(|(board, seed)| {
+                    simulation_phase(board, &self.config, &mut StdRng::seed_from_u64(seed))
+                })
+                .collect();
+        };
+        let batch_size = boards.len();
+        for (index, (board, seed)) in boards.into_iter().zip(seeds).enumerate() {
+            jobs.send((index, board, seed)).unwrap();
+        }
+        let mut rollouts: Vec<Option<Rollout<G>>> = Vec::new();
+        rollouts.resize_with(batch_size, || None);
+        for _ in 0..batch_size {
+            let (index, rollout) = self.results.recv().unwrap();
+            rollouts[index] = Some(rollout);
+        }
+        rollouts.into_iter().map(Option::unwrap).collect()
+    }
+}
@@ -0,0 +1,95 @@
+use super::{Game, Step, Tree};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Reports on a running search to `report`, which can hand the reports on to a channel.
+#[derive(Clone)]
+pub struct Progress<G: Game> {
+    pub interval: Interval,
+    /// Length of the principal variation in the reports.
+    pub line_length: usize,
+    pub report: ReportFn<G>,
+}
+
+/// Receives the reports of a running search, from whichever thread is searching.
+pub type ReportFn<G> = Arc<dyn Fn(&Report<G>) + Send + Sync>;
+
+/// How often a running search reports.
+#[derive(Clone, Copy, Debug)]
+pub enum Interval {
+    Iterations(usize),
+    Time(Duration),
+}
+
+/// The state of a running search. With [`Parallelism::Root`](super::Parallelism::Root) it only
+/// covers the tree searched on the calling thread.
+#[derive(Clone, Debug)]
+pub struct Report<G: Game> {
+    /// Iterations of this search so far.
+    pub iterations: usize,
+    pub elapsed: Duration,
+    pub iterations_per_second: f64,
+    /// Number of nodes in the tree.
+    pub nodes: usize,
+    /// The most visited line, see [`Tree::principal_variation`].
+    pub principal_variation: Vec<Step<G>>,
+}
+
+impl<G: Game> Report<G> {
+    /// The most visited move of the root, which is not always the one the engine ends up playing.
+    pub fn best_move(&self) -> Option<&G::Move> {
+        self.principal_variation.first().map(|step| &step.the_move)
+    }
+
+    /// The win rate of [`Report::best_move`] for the player to move.
+    pub fn win_rate(&self) -> Option<f64> {
+        self.principal_variation.first().map(|step| step.win_rate)
+    }
+}
+
+/// Sends the reports of one search when they are due, shared by all threads searching the tree.
+pub(super) struct Reporter<'a, G: Game> {
+    progress: Option<&'a Progress<G>>,
+    start: Instant,
+    /// The iterations and the time of the last report.
+    last: Mutex<(usize, Instant)>,
+}
+
+impl<'a, G: Game> Reporter<'a, G> {
+    pub(super) fn new(progress: Option<&'a Progress<G>>) -> Self {
+        let start = Instant::now();
+        Reporter {
+            progress,
+            start,
+            last: Mutex::new((0, start)),
+        }
+    }
+
+    /// Reports on `tree` if a report is due after `iterations`.
+    pub(super) fn tick(&self, iterations: usize, tree: &Tree<G>) {
+        let Some(progress) = self.progress else {
+            return;
+        };
+        // another thread is already reporting
+        let Ok(mut last) = self.last.try_lock() else {
+            return;
+        };
+        let due = match progress.interval {
+            Interval::Iterations(interval) => iterations >= last.0 + interval,
+            Interval::Time(interval) => last.1.elapsed() >= interval,
+        };
+        if !due {
+            return;
+        }
+        let now = Instant::now();
+        *last = (iterations, now);
+        let elapsed = now.duration_since(self.start);
+        (progress.report)(&Report {
+            iterations,
+            elapsed,
+            iterations_per_second: iterations as f64 / elapsed.as_secs_f64().max(1e-9),
+            nodes: tree.len(),
+            principal_variation: tree.principal_variation(progress.line_length),
+        });
+    }
+}
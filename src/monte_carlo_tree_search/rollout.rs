@@ -0,0 +1,66 @@
+use super::Game;
+use rand::{Rng, RngCore};
+
+/// Picks the moves of a rollout. Games can implement it for a heavy playout of their own,
+/// the built-in policies work on top of [`Game::move_weights`].
+pub trait RolloutPolicy<G: Game>: Send + Sync {
+    /// The move to play next on `board`, `None` if there is none. Any randomness comes from `rng`,
+    /// so seeded searches repeat themselves.
+    fn choose_move(&self, board: &G, rng: &mut dyn RngCore) -> Option<G::Move>;
+}
+
+/// Plays [`Game::get_random_move`], the cheapest way to finish a game.
+pub struct Uniform;
+
+impl<G: Game> RolloutPolicy<G> for Uniform {
+    fn choose_move(&self, board: &G, rng: &mut dyn RngCore) -> Option<G::Move> {
+        board.get_random_move(rng)
+    }
+}
+
+/// Plays the move with the highest weight, or a random one with probability `epsilon`.
+pub struct EpsilonGreedy {
+    pub epsilon: f64,
+}
+
+impl<G: Game> RolloutPolicy<G> for EpsilonGreedy {
+    fn choose_move(&self, board: &G, rng: &mut dyn RngCore) -> Option<G::Move> {
+        if rng.gen_bool(self.epsilon) {
+            return board.get_random_move(rng);
+        }
+        let mut moves = board.all_legal_moves();
+        let weights = board.move_weights(&moves);
+        let best_weight = weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        // ties are broken at random, otherwise uniform weights would always play the first move
+        let best: Vec<usize> = (0..moves.len())
+            .filter(|&index| weights[index] == best_weight)
+            .collect();
+        if best.is_empty() {
+            return None;
+        }
+        Some(moves.swap_remove(best[rng.gen_range(0..best.len())]))
+    }
+}
+
+/// Plays a move with a probability proportional to its weight.
+pub struct Weighted;
+
+impl<G: Game> RolloutPolicy<G> for Weighted {
+    fn choose_move(&self, board: &G, rng: &mut dyn RngCore) -> Option<G::Move> {
+        let mut moves = board.all_legal_moves();
+        let weights = board.move_weights(&moves);
+        let total: f64 = weights.iter().sum();
+        if moves.is_empty() || total <= 0.0 {
+            return board.get_random_move(rng);
+        }
+        let mut remaining = rng.gen_range(0.0..total);
+        let index = weights
+            .iter()
+            .position(|&weight| {
+                remaining -= weight;
+                remaining < 0.0
+            })
+            .unwrap_or(moves.len() - 1);
+        Some(moves.swap_remove(index))
+    }
+}
@@ -0,0 +1,159 @@
+use std::str::FromStr;
+
+/// How the selection phase scores the children of a node, the best scoring one is descended into.
+#[derive(Clone, Copy, Debug)]
+pub enum SelectionPolicy {
+    /// UCB1 with the given exploration constant, `sqrt(2)` being the textbook value.
+    /// Children that were never visited are tried first.
+    Uct { exploration: f64 },
+    /// UCB1-Tuned, which bounds the exploration of a child by the variance of its results.
+    /// Children that were never visited are tried first.
+    Ucb1Tuned,
+    /// The AlphaZero formula, which hands out the exploration by the move priors of the game.
+    /// Children that were never visited are valued at `first_play_urgency`.
+    Puct {
+        exploration: f64,
+        first_play_urgency: f64,
+    },
+}
+
+impl Default for SelectionPolicy {
+    fn default() -> Self {
+        SelectionPolicy::Uct {
+            exploration: std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+/// Rapid action value estimation: the value of a child is blended with its all-moves-as-first
+/// value, which counts every rollout in which the move was played later on. The weight of the
+/// latter drops with the visits of the child.
+#[derive(Clone, Copy, Debug)]
+pub struct Rave {
+    /// The number of visits at which both values weigh the same.
+    pub equivalence: f64,
+}
+
+impl Default for Rave {
+    fn default() -> Self {
+        Rave {
+            equivalence: 1000.0,
+        }
+    }
+}
+
+impl Rave {
+    pub(super) fn blend(&self, mean: f64, amaf_mean: f64, visits: f64) -> f64 {
+        let beta = (self.equivalence / (3.0 * visits + self.equivalence)).sqrt();
+        (1.0 - beta) * mean + beta * amaf_mean
+    }
+}
+
+/// Progressive widening: a node visited `n` times may only have `coefficient · n^exponent`
+/// children. The moves are unlocked from the highest [`Game::move_weight`](super::Game::move_weight) down.
+#[derive(Clone, Copy, Debug)]
+pub struct ProgressiveWidening {
+    pub coefficient: f64,
+    pub exponent: f64,
+}
+
+impl Default for ProgressiveWidening {
+    fn default() -> Self {
+        ProgressiveWidening {
+            coefficient: 2.0,
+            exponent: 0.5,
+        }
+    }
+}
+
+impl ProgressiveWidening {
+    pub(super) fn allowed_children(&self, visits: u32) -> usize {
+        (self.coefficient * (visits.max(1) as f64).powf(self.exponent)).ceil() as usize
+    }
+}
+
+/// What the selection phase knows about one child, with values between zero and one.
+pub(super) struct Candidate {
+    /// Sum of the results of the child, for the player choosing it.
+    pub value_sum: f64,
+    /// Sum of the squared results of the child.
+    pub squares_sum: f64,
+    /// Visits of the child node, shared between all its parents.
+    pub visits: f64,
+    /// Visits through the edge leading to the child.
+    pub edge_visits: f64,
+    pub prior: f64,
+}
+
+impl SelectionPolicy {
+    /// Whether the policy needs [`Game::move_weight`](super::Game::move_weight) for every edge.
+    pub(super) fn uses_priors(&self) -> bool {
+        matches!(self, SelectionPolicy::Puct { .. })
+    }
+
+    pub(super) fn score(&self, parent_visits: f64, candidate: &Candidate) -> f64 {
+        let unvisited = candidate.edge_visits == 0.0;
+        match *self {
+            SelectionPolicy::Uct { exploration } => {
+                if unvisited {
+                    return f64::INFINITY;
+                }
+                let mean = candidate.value_sum / candidate.visits;
+                mean + exploration * (parent_visits.max(1.0).ln() / candidate.edge_visits).sqrt()
+            }
+            SelectionPolicy::Ucb1Tuned => {
+                if unvisited {
+                    return f64::INFINITY;
+                }
+                let mean = candidate.value_sum / candidate.visits;
+                let log_visits = parent_visits.max(1.0).ln();
+                let variance = candidate.squares_sum / candidate.visits - mean * mean
+                    + (2.0 * log_visits / candidate.edge_visits).sqrt();
+                mean + (log_visits / candidate.edge_visits * variance.min(0.25)).sqrt()
+            }
+            SelectionPolicy::Puct {
+                exploration,
+                first_play_urgency,
+            } => {
+                let mean = if unvisited {
+                    first_play_urgency
+                } else {
+                    candidate.value_sum / candidate.visits
+                };
+                mean + exploration * candidate.prior * parent_visits.sqrt()
+                    / (1.0 + candidate.edge_visits)
+            }
+        }
+    }
+}
+
+/// Parses `uct`, `uct:<exploration>`, `ucb1-tuned`, `puct` and `puct:<exploration>:<first play urgency>`.
+impl FromStr for SelectionPolicy {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.split(':');
+        let name = parts.next().unwrap_or_default();
+        let mut parameter = |default: f64| match parts.next() {
+            None => Ok(default),
+            Some(number) => number
+                .parse::<f64>()
+                .map_err(|error| format!("invalid parameter {number:?} for {name}: {error}")),
+        };
+        let policy = match name {
+            "uct" => SelectionPolicy::Uct {
+                exploration: parameter(std::f64::consts::SQRT_2)?,
+            },
+            "ucb1-tuned" => SelectionPolicy::Ucb1Tuned,
+            "puct" => SelectionPolicy::Puct {
+                exploration: parameter(1.5)?,
+                first_play_urgency: parameter(0.5)?,
+            },
+            _ => return Err(format!("unknown selection policy {name:?}")),
+        };
+        match parts.next() {
+            None => Ok(policy),
+            Some(_) => Err(format!("too many parameters for {name}")),
+        }
+    }
+}
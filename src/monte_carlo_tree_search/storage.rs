@@ -0,0 +1,418 @@
+use super::{decode_proof, encode_proof, load_f64, Edge, Game, Node, NodeId, Origin, Tree, ROOT};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+
+const HEADER: &str = "koi-tree 1";
+
+/// Writes `tree` as text: the root board in [`Game::notation`], then every node with its
+/// statistics, the moves it has left to expand and its edges, and finally the transposition table.
+/// Fails with [`io::ErrorKind::Unsupported`] for games without a notation.
+pub fn save_tree<G: Game>(tree: &Tree<G>, out: &mut impl Write) -> io::Result<()> {
+    let mut boards = Boards::new(tree);
+    let Some(notation) = boards.root.notation() else {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the game has no notation to save its boards in",
+        ));
+    };
+    writeln!(out, "{HEADER}")?;
+    writeln!(out, "board {notation}")?;
+    for (id, node) in tree.nodes.iter().enumerate() {
+        let board = boards.take(tree, id);
+        writeln!(
+            out,
+            "node {} {} {} {} {} {} {} {}",
+            node.simulations(),
+            node.win_count(),
+            node.draw_count(),
+            node.estimate_count(),
+            node.reward_sum(),
+            load_f64(&node.reward_squares),
+            encode_proof(node.proven()),
+            node.total_weight
+        )?;
+        for possible_move in &node.possible_moves {
+            writeln!(out, "possible {}", board.move_notation(possible_move))?;
+        }
+        for edge in &node.children {
+            writeln!(
+                out,
+                "edge {} {} {} {} {} {} {}",
+                edge.node,
+                edge.visits(),
+                edge.symmetry,
+                edge.prior,
+                edge.amaf_visits.load(Ordering::Relaxed),
+                load_f64(&edge.amaf_value),
+                board.move_notation(&edge.the_move)
+            )?;
+        }
+    }
+    // sorted, so the same tree is always written the same way
+    let mut table: Vec<_> = tree.table.iter().map(|(&key, &id)| (id, key)).collect();
+    table.sort_unstable();
+    for (id, key) in table {
+        writeln!(out, "table {key} {id}")?;
+    }
+    Ok(())
+}
+
+/// Reads a tree written by [`save_tree`]. Every move is replayed on the board it belongs to and has
+/// to be legal there, a child reached from several parents has to be the same position from all
+/// of them, and the table may only list a node under its hash or the hash of its canonical form.
+/// A node may not count more results than simulations.
+pub fn load_tree<G: Game>(input: impl BufRead) -> Result<Tree<G>, String> {
+    let mut lines = input.lines().enumerate();
+    let mut next_line = || -> Result<Option<(usize, String)>, String> {
+        match lines.next() {
+            None => Ok(None),
+            Some((index, line)) => line
+                .map(|line| Some((index + 1, line)))
+                .map_err(|error| error.to_string()),
+        }
+    };
+    match next_line()? {
+        Some((_, header)) if header == HEADER => {}
+        _ => return Err(format!("not a saved tree, it has to start with {HEADER:?}")),
+    }
+    let Some((number, board_line)) = next_line()? else {
+        return Err("the board is missing".to_string());
+    };
+    let root_board = board_line
+        .strip_prefix("board ")
+        .and_then(G::from_notation)
+        .ok_or(format!("line {number}: not a board of this game"))?;
+    let mut records: Vec<NodeRecord> = Vec::new();
+    let mut table = HashMap::new();
+    while let Some((number, line)) = next_line()? {
+        let error = |what: &str| format!("line {number}: {what}");
+        let (kind, rest) = line.split_once(' ').unwrap_or((&line, ""));
+        match kind {
+            "node" => records.push(NodeRecord::parse(rest).ok_or(error("invalid node"))?),
+            "possible" => records
+                .last_mut()
+                .ok_or(error("a move before the first node"))?
+                .possible_moves
+                .push(rest.to_string()),
+            "edge" => records
+                .last_mut()
+                .ok_or(error("an edge before the first node"))?
+                .edges
+                .push(EdgeRecord::parse(rest).ok_or(error("invalid edge"))?),
+            "table" => {
+                let mut fields = rest.split(' ');
+                let key = parse::<u64>(&mut fields).ok_or(error("invalid table entry"))?;
+                let id = parse::<NodeId>(&mut fields).ok_or(error("invalid table entry"))?;
+                table.insert(key, id);
+            }
+            _ => return Err(error("unknown line")),
+        }
+    }
+    if records.is_empty() {
+        return Err("the tree has no root".to_string());
+    }
+    if table.values().any(|&id| id >= records.len()) {
+        return Err("the table points to a node that does not exist".to_string());
+    }
+
+    // the board of a node is known once its first parent is read, parents come before their children
+    let count = records.len();
+    let mut boards: Vec<Option<G>> = vec![None; count];
+    let mut hashes: Vec<Option<u64>> = vec![None; count];
+    let mut origins: Vec<Option<(Origin<G>, usize)>> = Vec::new();
+    origins.resize_with(count, || None);
+    hashes[ROOT] = Some(root_board.zobrist_hash());
+    boards[ROOT] = Some(root_board.clone());
+    origins[ROOT] = Some((Origin::Root(root_board), 0));
+    // the table is keyed by canonical forms when symmetries are merged, those are only worked out
+    // for the nodes it lists
+    let listed: HashSet<NodeId> = table.values().copied().collect();
+    let mut canonical_hashes = HashMap::new();
+    let mut nodes = Vec::with_capacity(count);
+    for (id, record) in records.into_iter().enumerate() {
+        let error = |what: String| format!("node {id}: {what}");
+        let board = boards[id]
+            .take()
+            .ok_or(error("not reachable from an earlier node".to_string()))?;
+        let (origin, depth) = origins[id].take().unwrap();
+        if listed.contains(&id) {
+            canonical_hashes.insert(id, board.canonical().0.zobrist_hash());
+        }
+        let legal_moves: HashMap<String, G::Move> = board
+            .all_legal_moves()
+            .into_iter()
+            .map(|a_move| (board.move_notation(&a_move), a_move))
+            .collect();
+        let legal = |notation: &String| {
+            legal_moves
+                .get(notation)
+                .cloned()
+                .ok_or(error(format!("{notation} is not a legal move")))
+        };
+        let possible_moves = record
+            .possible_moves
+            .iter()
+            .map(legal)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut children = Vec::with_capacity(record.edges.len());
+        for edge in record.edges {
+            let the_move = legal(&edge.the_move)?;
+            let mut child_board = board.clone();
+            child_board.apply_move(the_move.clone());
+            if edge.symmetry >= child_board.symmetry_count() {
+                return Err(error(format!(
+                    "{} has no symmetry {}",
+                    edge.the_move, edge.symmetry
+                )));
+            }
+            if edge.symmetry != 0 {
+                child_board = child_board.transformed(edge.symmetry);
+            }
+            if edge.node >= count {
+                return Err(error(format!(
+                    "edge to node {} that does not exist",
+                    edge.node
+                )));
+            }
+            match hashes[edge.node] {
+                Some(hash) if hash != child_board.zobrist_hash() => {
+                    return Err(error(format!(
+                        "{} leads to another position than node {}",
+                        edge.the_move, edge.node
+                    )));
+                }
+                Some(_) => {}
+                None => {
+                    hashes[edge.node] = Some(child_board.zobrist_hash());
+                    origins[edge.node] = Some((
+                        Origin::Parent(id, the_move.clone(), edge.symmetry),
+                        depth + 1,
+                    ));
+                    boards[edge.node] = Some(child_board);
+                }
+            }
+            let mut new_edge = Edge::new(the_move, edge.node, edge.symmetry, edge.prior);
+            new_edge.visits = AtomicU32::new(edge.visits);
+            new_edge.amaf_visits = AtomicU32::new(edge.amaf_visits);
+            new_edge.amaf_value = AtomicU64::new(edge.amaf_value.to_bits());
+            children.push(new_edge);
+        }
+        nodes.push(Node {
+            simulations: AtomicU32::new(record.simulations),
+            win_count: AtomicU32::new(record.win_count),
+            draw_count: AtomicU32::new(record.draw_count),
+            estimate_count: AtomicU32::new(record.estimate_count),
+            reward_sum: AtomicU64::new(record.reward_sum.to_bits()),
+            reward_squares: AtomicU64::new(record.reward_squares.to_bits()),
+            virtual_loss: AtomicU32::new(0),
+            proven: AtomicU8::new(encode_proof(decode_proof(record.proven))),
+            possible_moves,
+            children,
+            origin,
+            depth,
+            board: None,
+            hash: hashes[id].unwrap(),
+            total_weight: record.total_weight,
+        });
+    }
+    for (key, &id) in &table {
+        if *key != hashes[id].unwrap() && canonical_hashes.get(&id) != Some(key) {
+            return Err(format!(
+                "the table lists node {id} under {key}, which is not its position"
+            ));
+        }
+    }
+    Ok(Tree { nodes, table })
+}
+
+/// Hands out the boards of the nodes in the order of their ids, keeping only the boards of the
+/// children not reached yet.
+struct Boards<G: Game> {
+    root: G,
+    pending: HashMap<NodeId, G>,
+}
+
+impl<G: Game> Boards<G> {
+    fn new(tree: &Tree<G>) -> Self {
+        Boards {
+            root: tree.root_board().clone(),
+            pending: HashMap::new(),
+        }
+    }
+
+    fn take(&mut self, tree: &Tree<G>, id: NodeId) -> G {
+        let board = if id == ROOT {
+            self.root.clone()
+        } else {
+            self.pending.remove(&id).unwrap()
+        };
+        for edge in &tree.nodes[id].children {
+            if matches!(tree.nodes[edge.node].origin, Origin::Parent(parent, ..) if parent == id) {
+                let mut child_board = board.clone();
+                child_board.apply_move(edge.the_move.clone());
+                if edge.symmetry != 0 {
+                    child_board = child_board.transformed(edge.symmetry);
+                }
+                self.pending.insert(edge.node, child_board);
+            }
+        }
+        board
+    }
+}
+
+struct NodeRecord {
+    simulations: u32,
+    win_count: u32,
+    draw_count: u32,
+    estimate_count: u32,
+    reward_sum: f64,
+    reward_squares: f64,
+    proven: u8,
+    total_weight: f64,
+    possible_moves: Vec<String>,
+    edges: Vec<EdgeRecord>,
+}
+
+impl NodeRecord {
+    fn parse(text: &str) -> Option<Self> {
+        let mut fields = text.split(' ');
+        let record = NodeRecord {
+            simulations: parse(&mut fields)?,
+            win_count: parse(&mut fields)?,
+            draw_count: parse(&mut fields)?,
+            estimate_count: parse(&mut fields)?,
+            reward_sum: parse(&mut fields)?,
+            reward_squares: parse(&mut fields)?,
+            proven: parse(&mut fields)?,
+            total_weight: parse(&mut fields)?,
+            possible_moves: Vec::new(),
+            edges: Vec::new(),
+        };
+        let results = u64::from(record.win_count)
+            + u64::from(record.draw_count)
+            + u64::from(record.estimate_count);
+        (fields.next().is_none() && results <= u64::from(record.simulations)).then_some(record)
+    }
+}
+
+struct EdgeRecord {
+    node: NodeId,
+    visits: u32,
+    symmetry: usize,
+    prior: f64,
+    amaf_visits: u32,
+    amaf_value: f64,
+    the_move: String,
+}
+
+impl EdgeRecord {
+    fn parse(text: &str) -> Option<Self> {
+        // the move comes last, its notation may contain spaces
+        let mut fields = text.splitn(7, ' ');
+        Some(EdgeRecord {
+            node: parse(&mut fields)?,
+            visits: parse(&mut fields)?,
+            symmetry: parse(&mut fields)?,
+            prior: parse(&mut fields)?,
+            amaf_visits: parse(&mut fields)?,
+            amaf_value: parse(&mut fields)?,
+            the_move: fields.next()?.to_string(),
+        })
+    }
+}
+
+fn parse<'a, T: FromStr>(fields: &mut impl Iterator<Item = &'a str>) -> Option<T> {
+    fields.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monte_carlo_tree_search::{create_root_node, engine, Config, Mode};
+    use crate::tic_tac_toe::Board;
+
+    fn saved_tree() -> String {
+        let config = Config {
+            seed: Some(5),
+            ..Config::default()
+        };
+        let (tree, _) = engine(
+            create_root_node(Board::empty()),
+            Mode::Iterations(300),
+            &config,
+        );
+        let mut saved = Vec::new();
+        save_tree(&tree, &mut saved).unwrap();
+        String::from_utf8(saved).unwrap()
+    }
+
+    /// Replaces the `nth` line starting with `kind` by what `change` makes of its fields.
+    fn changed(saved: &str, kind: &str, nth: usize, change: impl Fn(&mut Vec<String>)) -> String {
+        let mut lines: Vec<String> = saved.lines().map(str::to_string).collect();
+        let line = lines
+            .iter_mut()
+            .filter(|line| line.starts_with(kind))
+            .nth(nth)
+            .unwrap();
+        let mut fields: Vec<String> = line.splitn(8, ' ').map(str::to_string).collect();
+        change(&mut fields);
+        *line = fields.join(" ");
+        lines.join("\n")
+    }
+
+    fn load_error(saved: &str) -> String {
+        match load_tree::<Board>(saved.as_bytes()) {
+            Ok(_) => panic!("the tree was accepted"),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn saving_a_loaded_tree_gives_the_same_text() {
+        let saved = saved_tree();
+        let tree = load_tree::<Board>(saved.as_bytes()).unwrap();
+        let mut saved_again = Vec::new();
+        save_tree(&tree, &mut saved_again).unwrap();
+        assert_eq!(String::from_utf8(saved_again).unwrap(), saved);
+    }
+
+    #[test]
+    fn rejects_an_illegal_move() {
+        // the center is taken on the empty board
+        let saved = changed(&saved_tree(), "edge ", 0, |fields| {
+            fields.truncate(7);
+            fields.push("Place(1, 1)".to_string());
+        });
+        assert!(load_error(&saved).contains("is not a legal move"));
+    }
+
+    #[test]
+    fn rejects_an_edge_to_another_position() {
+        let saved = saved_tree();
+        let first_child = saved
+            .lines()
+            .find(|line| line.starts_with("edge "))
+            .unwrap();
+        let first_child = first_child.split(' ').nth(1).unwrap().to_string();
+        let saved = changed(&saved, "edge ", 1, |fields| fields[1] = first_child.clone());
+        assert!(load_error(&saved).contains("leads to another position"));
+    }
+
+    #[test]
+    fn rejects_a_wrong_table_key() {
+        let saved = changed(&saved_tree(), "table ", 0, |fields| {
+            fields[1] = (fields[1].parse::<u64>().unwrap() ^ 1).to_string();
+        });
+        assert!(load_error(&saved).contains("is not its position"));
+    }
+
+    #[test]
+    fn rejects_more_results_than_simulations() {
+        let saved = changed(&saved_tree(), "node ", 0, |fields| {
+            fields[2] = (fields[1].parse::<u32>().unwrap() + 1).to_string();
+        });
+        assert!(load_error(&saved).contains("invalid node"));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::monte_carlo_tree_search::{Output, Player};
+use crate::monte_carlo_tree_search::{zobrist_key, Game, Output, Player};
+use rand::{Rng, RngCore};
 use std::ops::Div;
 
 #[derive(Clone, Copy, PartialEq)]
@@ -7,9 +8,9 @@ pub enum Piece {
     O,
 }
 
-impl Into<Player> for Piece {
-    fn into(self) -> Player {
-        match self {
+impl From<Piece> for Player {
+    fn from(piece: Piece) -> Self {
+        match piece {
             Piece::X => Player::Guest,
             Piece::O => Player::Host,
         }
@@ -54,8 +55,12 @@ impl Board {
             next_to_move: Piece::X,
         }
     }
+}
+
+impl Game for Board {
+    type Move = Move;
 
-    pub fn finished(&self, player: Player) -> Option<Output> {
+    fn finished(&self, player: Player) -> Option<Output> {
         if winning_three(self.cells[0], self.cells[4], self.cells[8]) {
             return if self.cells[4].unwrap() == player.into() {
                 Some(Output::Win)
@@ -72,17 +77,17 @@ impl Board {
         }
         for i in 0..3 {
             if winning_three(
-                self.cells[0 + 3 * i],
+                self.cells[3 * i],
                 self.cells[1 + 3 * i],
                 self.cells[2 + 3 * i],
             ) {
-                return if self.cells[0 + 3 * i].unwrap() == player.into() {
+                return if self.cells[3 * i].unwrap() == player.into() {
                     Some(Output::Win)
                 } else {
                     Some(Output::Loss)
                 };
             }
-            if winning_three(self.cells[0 + i], self.cells[3 + i], self.cells[6 + i]) {
+            if winning_three(self.cells[i], self.cells[3 + i], self.cells[6 + i]) {
                 return if self.cells[i].unwrap() == player.into() {
                     Some(Output::Win)
                 } else {
@@ -91,14 +96,15 @@ impl Board {
             }
         }
         for cell in self.cells {
-            if cell.is_none() {
-                return None;
-            }
+            cell?;
         }
         Some(Output::Draw)
     }
 
-    pub fn all_legal_moves(&self) -> Vec<Move> {
+    fn all_legal_moves(&self) -> Vec<Move> {
+        if self.finished(self.next_to_move()).is_some() {
+            return Vec::new();
+        }
         let mut list = Vec::new();
         for (index, cell) in self.cells.iter().enumerate() {
             if cell.is_none() {
@@ -108,7 +114,7 @@ impl Board {
         list
     }
 
-    pub fn apply_move(&mut self, a_move: Move) {
+    fn apply_move(&mut self, a_move: Move) {
         let Move::Place(x, y) = a_move;
         self.cells[x + 3 * y] = Some(self.next_to_move);
         self.next_to_move = match self.next_to_move {
@@ -117,12 +123,153 @@ impl Board {
         };
     }
 
-    pub fn next_to_move(&self) -> Player {
+    fn next_to_move(&self) -> Player {
         self.next_to_move.into()
     }
+
+    fn get_random_move(&self, rng: &mut dyn RngCore) -> Option<Move> {
+        let all_moves = self.all_legal_moves();
+        if all_moves.is_empty() {
+            None
+        } else {
+            Some(all_moves[rng.gen_range(0..all_moves.len())])
+        }
+    }
+
+    fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (index, cell) in self.cells.iter().enumerate() {
+            match cell {
+                None => {}
+                Some(Piece::X) => hash ^= zobrist_key(index as u64 * 2),
+                Some(Piece::O) => hash ^= zobrist_key(index as u64 * 2 + 1),
+            }
+        }
+        if self.next_to_move == Piece::O {
+            hash ^= zobrist_key(18);
+        }
+        hash
+    }
+
+    fn symmetry_count(&self) -> usize {
+        SYMMETRIES
+    }
+
+    fn transformed(&self, symmetry: usize) -> Self {
+        let mut board = self.clone();
+        for (index, cell) in self.cells.iter().enumerate() {
+            let (x, y) = transform_square(index % 3, index / 3, 2, symmetry);
+            board.cells[x + 3 * y] = *cell;
+        }
+        board
+    }
+
+    fn inverse_symmetry(&self, symmetry: usize) -> usize {
+        inverse_symmetry(symmetry)
+    }
+
+    fn transform_move(&self, a_move: &Move, symmetry: usize) -> Move {
+        let Move::Place(x, y) = *a_move;
+        let (x, y) = transform_square(x, y, 2, symmetry);
+        Move::Place(x, y)
+    }
+
+    fn notation(&self) -> Option<String> {
+        let mut text: String = self.cells.iter().map(|&cell| piece_char(cell)).collect();
+        text.push(piece_char(Some(self.next_to_move)));
+        Some(text)
+    }
+
+    fn from_notation(text: &str) -> Option<Self> {
+        let chars: Vec<char> = text.chars().collect();
+        if chars.len() != 10 {
+            return None;
+        }
+        let mut board = Board::empty();
+        for (cell, &c) in board.cells.iter_mut().zip(&chars) {
+            *cell = char_piece(c)?;
+        }
+        board.next_to_move = char_piece(chars[9])??;
+        Some(board)
+    }
+}
+
+/// One cell in [`Game::notation`]: `x`, `o` or `.` when empty.
+fn piece_char(cell: Cell) -> char {
+    match cell {
+        None => '.',
+        Some(Piece::X) => 'x',
+        Some(Piece::O) => 'o',
+    }
+}
+
+/// Reads a cell written by [`piece_char`].
+fn char_piece(c: char) -> Option<Cell> {
+    match c {
+        '.' => Some(None),
+        'x' => Some(Some(Piece::X)),
+        'o' => Some(Some(Piece::O)),
+        _ => None,
+    }
+}
+
+/// The rotations and reflections of a square board.
+pub(crate) const SYMMETRIES: usize = 8;
+
+/// Maps the square `(x, y)` of a board whose coordinates go from `0` to `max` by one of its
+/// [`SYMMETRIES`], `0` being the identity.
+pub(crate) fn transform_square(x: usize, y: usize, max: usize, symmetry: usize) -> (usize, usize) {
+    match symmetry {
+        0 => (x, y),
+        1 => (max - y, x),
+        2 => (max - x, max - y),
+        3 => (y, max - x),
+        4 => (max - x, y),
+        5 => (x, max - y),
+        6 => (y, x),
+        7 => (max - y, max - x),
+        _ => unreachable!(),
+    }
+}
+
+/// The symmetry undoing one of the [`SYMMETRIES`], only the quarter turns are not their own inverse.
+pub(crate) fn inverse_symmetry(symmetry: usize) -> usize {
+    match symmetry {
+        1 => 3,
+        3 => 1,
+        _ => symmetry,
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for y in 0..3 {
+            for x in 0..3 {
+                write!(f, "|")?;
+                match self.cells[x + 3 * y] {
+                    None => write!(f, " ")?,
+                    Some(Piece::O) => write!(f, "o")?,
+                    Some(Piece::X) => write!(f, "x")?,
+                }
+            }
+            writeln!(f, "|")?;
+        }
+        std::fmt::Result::Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Move {
     Place(usize, usize),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monte_carlo_tree_search::check_symmetries;
+
+    #[test]
+    fn moves_follow_the_symmetries() {
+        check_symmetries(Board::empty(), 20, 9);
+    }
+}
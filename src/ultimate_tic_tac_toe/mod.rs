@@ -1,5 +1,6 @@
-use crate::monte_carlo_tree_search::{Output, Player};
-use rand::{thread_rng, Rng};
+use crate::monte_carlo_tree_search::{zobrist_key, Game, Output, Player};
+use crate::tic_tac_toe::{inverse_symmetry, transform_square, SYMMETRIES};
+use rand::{Rng, RngCore};
 
 #[derive(Clone)]
 pub struct Board {
@@ -160,7 +161,12 @@ impl Board {
             next: Player::Guest,
         }
     }
-    pub fn finished(&self, player: Player) -> Option<Output> {
+}
+
+impl Game for Board {
+    type Move = Move;
+
+    fn finished(&self, player: Player) -> Option<Output> {
         if three_winning_boards(
             self.sub_boards[0].state,
             self.sub_boards[4].state,
@@ -199,7 +205,7 @@ impl Board {
         }
         for i in 0..3 {
             if three_winning_boards(
-                self.sub_boards[0 + 3 * i].state,
+                self.sub_boards[3 * i].state,
                 self.sub_boards[1 + 3 * i].state,
                 self.sub_boards[2 + 3 * i].state,
             ) {
@@ -217,7 +223,7 @@ impl Board {
                 }
             }
             if three_winning_boards(
-                self.sub_boards[0 + i].state,
+                self.sub_boards[i].state,
                 self.sub_boards[3 + i].state,
                 self.sub_boards[6 + i].state,
             ) {
@@ -243,11 +249,11 @@ impl Board {
         Some(Output::Draw)
     }
 
-    pub fn all_legal_moves(&self) -> Vec<Move> {
+    fn all_legal_moves(&self) -> Vec<Move> {
         if self.finished(self.next).is_some() {
             return Vec::new();
         }
-        return if let Some(index) = self.forced_sub_board {
+        if let Some(index) = self.forced_sub_board {
             let mut prime_moves = self.sub_boards[index].all_legal_moves();
             prime_moves
                 .iter_mut()
@@ -272,10 +278,10 @@ impl Board {
                 }
             }
             moves
-        };
+        }
     }
 
-    pub fn apply_move(&mut self, m: Move) {
+    fn apply_move(&mut self, m: Move) {
         let c = match self.next {
             Player::Host => Cell::O,
             Player::Guest => Cell::X,
@@ -295,13 +301,166 @@ impl Board {
         }
     }
 
-    pub fn next_to_move(&self) -> Player {
+    fn next_to_move(&self) -> Player {
         self.next
     }
 
-    pub fn get_random_move(&self) -> Option<Move> {
+    fn get_random_move(&self, rng: &mut dyn RngCore) -> Option<Move> {
         let mut all_moves = self.all_legal_moves();
-        Some(all_moves.remove(thread_rng().gen_range(0..all_moves.len())))
+        Some(all_moves.remove(rng.gen_range(0..all_moves.len())))
+    }
+
+    fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (index, sub_board) in self.sub_boards.iter().enumerate() {
+            for (cell_index, cell) in sub_board.cells.iter().enumerate() {
+                let piece = match cell {
+                    Cell::Empty => continue,
+                    Cell::X => 0,
+                    Cell::O => 1,
+                };
+                hash ^= zobrist_key(((index * 9 + cell_index) * 2 + piece) as u64);
+            }
+        }
+        hash ^= zobrist_key(162 + self.forced_sub_board.unwrap_or(9) as u64);
+        if self.next == Player::Host {
+            hash ^= zobrist_key(172);
+        }
+        hash
+    }
+
+    fn moves_left(&self) -> usize {
+        //each player fills half of the open cells, and most games are decided well before the board is full
+        let open_cells: usize = self
+            .sub_boards
+            .iter()
+            .filter(|sub_board| sub_board.state == State::NotFinished)
+            .map(|sub_board| {
+                sub_board
+                    .cells
+                    .iter()
+                    .filter(|&&cell| cell == Cell::Empty)
+                    .count()
+            })
+            .sum();
+        (open_cells / 4).max(1)
+    }
+
+    fn evaluate(&self, player: Player) -> f64 {
+        //a won sub-board counts by the number of lines through it, the center lies on four
+        let mut score: f64 = 0.0;
+        for (index, sub_board) in self.sub_boards.iter().enumerate() {
+            let lines = match index {
+                4 => 4.0,
+                0 | 2 | 6 | 8 => 3.0,
+                _ => 2.0,
+            };
+            match sub_board.state {
+                State::Win(owner) if owner == player => score += lines,
+                State::Win(_) => score -= lines,
+                State::NotFinished | State::Draw => {}
+            }
+        }
+        1.0 / (1.0 + (-score / 4.0).exp())
+    }
+
+    fn move_weight(&self, a_move: &Move) -> f64 {
+        let mut board = self.clone();
+        board.apply_move(*a_move);
+        let sub_board = (a_move.x / 3) + (a_move.y / 3) * 3;
+        if board.sub_boards[sub_board].state == State::Win(self.next) {
+            8.0
+        } else if board.forced_sub_board.is_none() {
+            //the opponent may play anywhere
+            0.5
+        } else {
+            1.0
+        }
+    }
+
+    fn symmetry_count(&self) -> usize {
+        SYMMETRIES
+    }
+
+    fn transformed(&self, symmetry: usize) -> Self {
+        // a symmetry of the whole board moves the sub boards the same way it moves the cells inside them
+        let transform_index = |index: usize| {
+            let (x, y) = transform_square(index % 3, index / 3, 2, symmetry);
+            x + 3 * y
+        };
+        let mut board = self.clone();
+        for (index, sub_board) in self.sub_boards.iter().enumerate() {
+            let new_sub_board = &mut board.sub_boards[transform_index(index)];
+            new_sub_board.state = sub_board.state;
+            for (cell_index, cell) in sub_board.cells.iter().enumerate() {
+                new_sub_board.cells[transform_index(cell_index)] = *cell;
+            }
+        }
+        board.forced_sub_board = self.forced_sub_board.map(transform_index);
+        board
+    }
+
+    fn inverse_symmetry(&self, symmetry: usize) -> usize {
+        inverse_symmetry(symmetry)
+    }
+
+    fn transform_move(&self, a_move: &Move, symmetry: usize) -> Move {
+        let (x, y) = transform_square(a_move.x, a_move.y, 8, symmetry);
+        Move { x, y }
+    }
+
+    fn notation(&self) -> Option<String> {
+        //the cells sub board by sub board, the forced sub board and the player to move
+        let mut text = String::with_capacity(83);
+        for sub_board in &self.sub_boards {
+            text.extend(sub_board.cells.iter().map(|cell| match cell {
+                Cell::Empty => '.',
+                Cell::X => 'x',
+                Cell::O => 'o',
+            }));
+        }
+        text.push(match self.forced_sub_board {
+            Some(index) => char::from_digit(index as u32, 10).unwrap(),
+            None => '-',
+        });
+        text.push(match self.next {
+            Player::Guest => 'x',
+            Player::Host => 'o',
+        });
+        Some(text)
+    }
+
+    fn from_notation(text: &str) -> Option<Self> {
+        let chars: Vec<char> = text.chars().collect();
+        if chars.len() != 83 {
+            return None;
+        }
+        let mut board = Board::empty();
+        for (index, sub_board) in board.sub_boards.iter_mut().enumerate() {
+            for (cell, c) in sub_board.cells.iter_mut().zip(&chars[index * 9..]) {
+                *cell = match c {
+                    '.' => Cell::Empty,
+                    'x' => Cell::X,
+                    'o' => Cell::O,
+                    _ => return None,
+                };
+            }
+            sub_board.update();
+        }
+        board.forced_sub_board = match chars[81] {
+            '-' => None,
+            c => Some(c.to_digit(9)? as usize),
+        };
+        board.next = match chars[82] {
+            'x' => Player::Guest,
+            'o' => Player::Host,
+            _ => return None,
+        };
+        Some(board)
+    }
+
+    fn move_notation(&self, a_move: &Move) -> String {
+        format!("{}{}", a_move.x, a_move.y)
     }
 }
 
@@ -318,7 +477,7 @@ impl std::fmt::Display for Board {
                     Cell::X => write!(f, "x")?,
                 }
             }
-            write!(f, "|\n")?;
+            writeln!(f, "|")?;
         }
         std::fmt::Result::Ok(())
     }
@@ -375,7 +534,7 @@ impl SubBoard {
         }
         for i in 0..3 {
             if three_winning_cells(
-                self.cells[0 + 3 * i],
+                self.cells[3 * i],
                 self.cells[1 + 3 * i],
                 self.cells[2 + 3 * i],
             ) {
@@ -386,7 +545,7 @@ impl SubBoard {
                 };
                 return;
             }
-            if three_winning_cells(self.cells[0 + i], self.cells[3 + i], self.cells[6 + i]) {
+            if three_winning_cells(self.cells[i], self.cells[3 + i], self.cells[6 + i]) {
                 match self.cells[i] {
                     Cell::X => self.state = State::Win(Player::Guest),
                     Cell::O => self.state = State::Win(Player::Host),
@@ -434,3 +593,14 @@ fn three_winning_boards(a: State, b: State, c: State) -> bool {
         _ => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monte_carlo_tree_search::check_symmetries;
+
+    #[test]
+    fn moves_follow_the_symmetries() {
+        check_symmetries(Board::empty(), 5, 81);
+    }
+}